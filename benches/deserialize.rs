@@ -0,0 +1,150 @@
+//! Deserialization throughput on representative documents, compared against serde_yaml.
+//!
+//! Performance budget: `saphyr_serde::de::from_str` should stay within 1.5x of
+//! `serde_yaml::from_str` on every corpus here. Run with `cargo bench --bench deserialize`.
+
+use std::{collections::BTreeMap, fmt::Write, hint::black_box};
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use serde::{Deserialize, de::DeserializeOwned};
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Manifest {
+    #[serde(rename = "apiVersion")]
+    api_version: String,
+    kind: String,
+    metadata: Metadata,
+    spec: Spec,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Metadata {
+    name: String,
+    labels: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Spec {
+    replicas: u32,
+    containers: Vec<Container>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Container {
+    name: String,
+    image: String,
+    ports: Vec<Port>,
+    env: Vec<EnvVar>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Port {
+    #[serde(rename = "containerPort")]
+    container_port: u16,
+    protocol: String,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct EnvVar {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Record {
+    id: u64,
+    name: String,
+    score: f64,
+    active: bool,
+}
+
+fn manifests(count: usize) -> String {
+    let mut yaml = String::from("manifests:\n");
+    for i in 0..count {
+        write!(
+            yaml,
+            r#"  - apiVersion: apps/v1
+    kind: Deployment
+    metadata:
+      name: service-{i}
+      labels:
+        app: service-{i}
+        tier: backend
+    spec:
+      replicas: 3
+      containers:
+        - name: app
+          image: "registry.example.com/service-{i}:1.2.3"
+          ports:
+            - containerPort: 8080
+              protocol: TCP
+          env:
+            - name: RUST_LOG
+              value: info
+            - name: PORT
+              value: "8080"
+"#
+        )
+        .unwrap();
+    }
+    yaml
+}
+
+fn deep_nesting(depth: usize) -> String {
+    let mut yaml = String::new();
+    for level in 0..depth {
+        writeln!(yaml, "{}level{level}:", "  ".repeat(level)).unwrap();
+    }
+    writeln!(yaml, "{}leaf: value", "  ".repeat(depth)).unwrap();
+    yaml
+}
+
+fn records(count: usize) -> String {
+    let mut yaml = String::new();
+    for i in 0..count {
+        writeln!(
+            yaml,
+            "- id: {i}\n  name: record-{i}\n  score: {}.5\n  active: {}",
+            i % 100,
+            i % 2 == 0
+        )
+        .unwrap();
+    }
+    yaml
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Manifests {
+    manifests: Vec<Manifest>,
+}
+
+fn compare<T: DeserializeOwned>(c: &mut Criterion, corpus: &str, input: &str) {
+    let mut group = c.benchmark_group("from_str");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_with_input(
+        BenchmarkId::new("saphyr_serde", corpus),
+        input,
+        |b, input| b.iter(|| saphyr_serde::de::from_str::<T>(black_box(input)).unwrap()),
+    );
+    group.bench_with_input(BenchmarkId::new("serde_yaml", corpus), input, |b, input| {
+        b.iter(|| serde_yaml::from_str::<T>(black_box(input)).unwrap())
+    });
+    group.finish();
+}
+
+fn from_str(c: &mut Criterion) {
+    compare::<Manifests>(c, "k8s_manifests", &manifests(200));
+    compare::<serde_json::Value>(c, "deep_nesting", &deep_nesting(100));
+    compare::<Vec<Record>>(c, "scalar_heavy", &records(5_000));
+}
+
+criterion_group!(benches, from_str);
+criterion_main!(benches);
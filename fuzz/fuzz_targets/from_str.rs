@@ -0,0 +1,42 @@
+#![no_main]
+
+//! Any input must produce either a value or an error, never a panic.
+
+use std::collections::BTreeMap;
+
+use libfuzzer_sys::fuzz_target;
+use saphyr_serde::{de::from_str, options::DeserializerOptions};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+enum Shape {
+    Unit,
+    Newtype(i64),
+    Tuple(u8, char),
+    Struct { name: String },
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Document {
+    name: Option<String>,
+    count: u32,
+    ratio: f32,
+    enabled: bool,
+    shapes: Vec<Shape>,
+    labels: BTreeMap<String, String>,
+    pair: Option<(i8, u128)>,
+}
+
+fuzz_target!(|data: &str| {
+    let _ = from_str::<serde_json::Value>(data);
+    let _ = from_str::<Document>(data);
+    let _ = from_str::<Vec<Shape>>(data);
+
+    let mut de = saphyr_serde::de::Deserializer::from_str_with_options(
+        data,
+        DeserializerOptions::strict().legacy_1_1(true),
+    );
+    let _ = serde_json::Value::deserialize(&mut de);
+});
@@ -0,0 +1,112 @@
+//! The parts of the `serde_yaml` API this crate can stand in for, so switching over is a matter
+//! of changing `serde_yaml::` paths to `saphyr_serde::compat::`.
+//!
+//! Only deserialization is covered: there is no `Value`, `Mapping`, `Number`, `to_string` or
+//! `apply_merge` here.
+
+use saphyr_parser::{Marker, ScanError, Span};
+use serde::de::{Deserialize, DeserializeOwned};
+
+use crate::position::byte_offset;
+pub use crate::{de::Deserializer, error::DeserializeError as Error};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// [`crate::from_str`], with the [`Location`] of an error counting bytes as `serde_yaml` does.
+pub fn from_str<'de, T>(s: &'de str) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    crate::from_str(s).map_err(|err| in_bytes(err, s))
+}
+
+/// [`crate::from_slice`], with the [`Location`] of an error counting bytes as `serde_yaml` does.
+pub fn from_slice<'de, T>(v: &'de [u8]) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    crate::from_slice(v).map_err(|err| match std::str::from_utf8(v) {
+        Ok(s) => in_bytes(err, s),
+        Err(_) => err,
+    })
+}
+
+/// Reads all of `reader` and deserializes it as [`from_slice`] does. Input that isn't UTF-8 is
+/// transcoded as [`crate::from_reader`] does, and then locations count characters instead.
+pub fn from_reader<R, T>(mut reader: R) -> Result<T>
+where
+    R: std::io::Read,
+    T: DeserializeOwned,
+{
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+    match std::str::from_utf8(&input) {
+        Ok(s) => from_str(s),
+        Err(_) => crate::from_slice_owned(&input),
+    }
+}
+
+/// Rewrites the index of the markers in `err` from a character count to a byte offset into
+/// `source`, which is what [`Location::index`] reports.
+fn in_bytes(mut err: Error, source: &str) -> Error {
+    let to_bytes = |marker: Marker| {
+        byte_offset(source, marker).map_or(marker, |index| {
+            Marker::new(index, marker.line(), marker.col())
+        })
+    };
+    let span_in_bytes =
+        |span: &mut Span| *span = Span::new(to_bytes(span.start), to_bytes(span.end));
+    match &mut err {
+        Error::UnexpectedElement { span, .. }
+        | Error::NumberParseError { span, .. }
+        | Error::BoolParseError { span, .. }
+        | Error::BinaryParseError { span }
+        | Error::TypeMismatch { span, .. }
+        | Error::UnknownTag { span, .. }
+        | Error::DuplicateKey { span, .. }
+        | Error::DepthLimitExceeded { span, .. }
+        | Error::LimitExceeded { span, .. }
+        | Error::ValidationFailed { span, .. } => span_in_bytes(span),
+        Error::InvalidLength { span, .. }
+        | Error::UnknownField { span, .. }
+        | Error::UnknownVariant { span, .. } => span.iter_mut().for_each(span_in_bytes),
+        Error::ScanError(scan) => {
+            *scan = ScanError::new(to_bytes(*scan.marker()), scan.info().to_string());
+        }
+        _ => {}
+    }
+    err
+}
+
+/// A position in the input, as returned by `serde_yaml::Error::location`. Lines and columns
+/// count from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Location {
+    index: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Location {
+    pub(crate) fn new(marker: saphyr_parser::Marker) -> Self {
+        Self {
+            index: marker.index(),
+            line: marker.line(),
+            column: marker.col() + 1,
+        }
+    }
+
+    /// The byte offset into the input, for errors from this module's functions. Errors from the
+    /// rest of the crate count characters here, as their markers do.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
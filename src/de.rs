@@ -1,75 +1,503 @@
-use std::str::FromStr;
+use std::{borrow::Cow, collections::VecDeque, marker::PhantomData, str::FromStr};
 
-use regex::{Regex, RegexSet};
-use saphyr_parser::Event;
+use saphyr_parser::{Event, ScalarStyle};
 use serde::{
     Deserialize,
-    de::{IntoDeserializer, Visitor},
+    de::{DeserializeOwned, DeserializeSeed, IgnoredAny, IntoDeserializer, Visitor},
 };
 
 use crate::{
-    error::{DeserializeError, Result},
+    encoding::{self, Encoding},
+    error::{DeserializeError, Limit, Result},
+    filter::{DocumentHead, FilterDocuments},
+    intrinsic::{Intrinsic, long_form},
     mapping::YamlMapping,
-    seq::YamlSequence,
-    variant::Enum,
+    options::{DeserializerOptions, UnknownTags},
+    position,
+    scalar::{
+        decode_base64, display_tag, is_core_tag, is_known_tag, is_null_scalar, local_tag,
+        parse_int, resolve_bool, resolve_float,
+    },
+    seq::{YamlSequence, YamlSet},
+    source_map::SourceMap,
+    tagged::TAGGED,
+    template::{TEMPLATE_OR, is_template},
+    validate::{Node, Path, Validator, matches},
+    variant::{Enum, match_variant},
+    warning::Warning,
 };
 
+/// How many consumed events are kept to give context to unexpected element errors.
+const RECENT_EVENTS: usize = 4;
+
+/// A consumed event, kept as its kind and span so its text can be found again in the input
+/// when an error needs it.
+struct RecentEvent {
+    kind: &'static str,
+    span: saphyr_parser::Span,
+}
+
+impl RecentEvent {
+    fn new(event: &Event<'_>, span: saphyr_parser::Span) -> Self {
+        let kind = match event {
+            Event::Nothing => "Nothing",
+            Event::StreamStart => "StreamStart",
+            Event::StreamEnd => "StreamEnd",
+            Event::DocumentStart(_) => "DocumentStart",
+            Event::DocumentEnd => "DocumentEnd",
+            Event::Alias(_) => "Alias",
+            Event::Scalar(..) => "Scalar",
+            Event::SequenceStart(..) => "SequenceStart",
+            Event::SequenceEnd => "SequenceEnd",
+            Event::MappingStart(..) => "MappingStart",
+            Event::MappingEnd => "MappingEnd",
+        };
+        Self { kind, span }
+    }
+
+    fn describe(&self, input: &str) -> String {
+        match position::slice(input, self.span) {
+            Some(text) if !text.is_empty() => format!("{} {:?}", self.kind, text),
+            _ => String::from(self.kind),
+        }
+    }
+}
+
+/// A callback registered with [`Deserializer::on_value`].
+type ValueCallback = Box<dyn FnMut(&str, saphyr_parser::Span)>;
+
 pub struct Deserializer<'de> {
     // This string starts with the input data and characters are truncated off
     // the beginning as data is parsed.
     yaml: saphyr_parser::Parser<'de, saphyr_parser::StrInput<'de>>,
-    boolean_re: RegexSet,
-    null_re: Regex,
+    pub(crate) input: &'de str,
+    pub(crate) options: DeserializerOptions,
+    recent_events: VecDeque<RecentEvent>,
+    /// Events read ahead of deserializing, by [`Self::read_ahead`], to be handed out again.
+    replay: VecDeque<(Event<'de>, saphyr_parser::Span)>,
+    input_len: usize,
+    depth: usize,
+    nodes: usize,
+    validators: Vec<(String, Validator)>,
+    source_map: Option<SourceMap>,
+    on_value: Option<ValueCallback>,
+    /// Where each value entered for `on_value`, innermost last.
+    value_starts: Vec<saphyr_parser::Marker>,
+    /// Set while the next node's tag names an enum variant, so it isn't reported as unknown.
+    variant_tag: bool,
+    /// Where the most recently consumed scalar or collection start ended.
+    last_end: saphyr_parser::Marker,
+    /// Where `input` starts in the caller's buffer, see [`Self::from_str_at`].
+    base: usize,
+    /// Where the most recently consumed event ended in `input`.
+    position: saphyr_parser::Marker,
+    pub(crate) path: Path,
 }
 
 impl<'de> Deserializer<'de> {
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(input: &'de str) -> Self {
+        Self::from_str_with_options(input, DeserializerOptions::default())
+    }
+
+    pub fn from_str_with_options(input: &'de str, options: DeserializerOptions) -> Self {
         let yaml = saphyr_parser::Parser::new_from_str(input);
-        let boolean_re = RegexSet::new([
-            r"^(y|Y|yes|Yes|YES|true|True|TRUE|on|On|ON|)$",
-            r"^(n|N|no|No|NO|false|False|FALSE|off|Off|OFF)$",
-        ])
-        .unwrap();
-        let null_re = Regex::new(r"^(null|Null|NULL|~)$").unwrap();
         Deserializer {
             yaml,
-            boolean_re,
-            null_re,
+            input,
+            options,
+            recent_events: VecDeque::with_capacity(RECENT_EVENTS),
+            replay: VecDeque::new(),
+            input_len: input.len(),
+            depth: 0,
+            nodes: 0,
+            validators: Vec::new(),
+            source_map: None,
+            on_value: None,
+            value_starts: Vec::new(),
+            variant_tag: false,
+            last_end: saphyr_parser::Marker::default(),
+            base: 0,
+            position: saphyr_parser::Marker::default(),
+            path: Path::default(),
+        }
+    }
+
+    /// Reads `input` from byte `offset` on, typically one returned by [`Self::byte_offset`]
+    /// after an earlier document. Spans in errors count from `offset`. Fails with an
+    /// [`InvalidInput`](std::io::ErrorKind::InvalidInput) IO error if `offset` is out of bounds
+    /// or not on a character boundary.
+    pub fn from_str_at(input: &'de str, offset: usize) -> Result<Self> {
+        Self::from_str_at_with_options(input, offset, DeserializerOptions::default())
+    }
+
+    pub fn from_str_at_with_options(
+        input: &'de str,
+        offset: usize,
+        options: DeserializerOptions,
+    ) -> Result<Self> {
+        let rest = input.get(offset..).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("byte offset {offset} is past the end or inside a character"),
+            )
+        })?;
+        let mut de = Self::from_str_with_options(rest, options);
+        de.base = offset;
+        Ok(de)
+    }
+
+    /// The byte offset just past the last event read, counted from the start of the buffer
+    /// given to [`Self::from_str_at`]. After a document ended with `...` this is where the
+    /// bytes following it begin.
+    pub fn byte_offset(&self) -> usize {
+        // Markers count characters, so go through the line and column to get bytes.
+        let offset = crate::position::byte_offset(self.input, self.position);
+        self.base + offset.unwrap_or(self.input.len())
+    }
+
+    /// Where the last event read ended, as a line and column in the input given to the
+    /// deserializer, for reporting how far reading got.
+    pub fn position(&self) -> saphyr_parser::Marker {
+        self.position
+    }
+
+    /// Start over on `input`, keeping the options, validators and the buffers allocated so far.
+    pub fn reset(&mut self, input: &'de str) {
+        self.yaml = saphyr_parser::Parser::new_from_str(input);
+        self.input = input;
+        self.recent_events.clear();
+        self.replay.clear();
+        self.input_len = input.len();
+        self.depth = 0;
+        self.nodes = 0;
+        self.variant_tag = false;
+        self.value_starts.clear();
+        self.last_end = saphyr_parser::Marker::default();
+        self.base = 0;
+        self.position = saphyr_parser::Marker::default();
+        self.path.clear();
+        if let Some(source_map) = &mut self.source_map {
+            *source_map = SourceMap::default();
+        }
+    }
+
+    /// Reads each document in the stream as a `T`, like serde_json's `StreamDeserializer`.
+    ///
+    /// ```
+    /// let input = "name: a\n---\nname: b\n";
+    /// let names: Vec<std::collections::HashMap<String, String>> =
+    ///     saphyr_serde::Deserializer::from_str(input)
+    ///         .into_iter()
+    ///         .collect::<Result<_, _>>()
+    ///         .unwrap();
+    /// assert_eq!(names[1]["name"], "b");
+    /// ```
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter<T>(self) -> StreamDeserializer<'de, T>
+    where
+        T: Deserialize<'de>,
+    {
+        StreamDeserializer {
+            de: self,
+            started: false,
+            finished: false,
+            output: PhantomData,
         }
     }
 
+    /// Like [`Self::into_iter`], but only deserializes the documents whose
+    /// [`DocumentHead`](crate::filter::DocumentHead), the scalar entries they start with,
+    /// satisfies `predicate`. The others are skipped without building a `T`.
+    ///
+    /// ```
+    /// #[derive(serde::Deserialize)]
+    /// struct Service {
+    ///     name: String,
+    /// }
+    ///
+    /// let input = "kind: Service\nname: web\n---\nkind: Secret\ndata: {}\n";
+    /// let services: Vec<Service> = saphyr_serde::Deserializer::from_str(input)
+    ///     .filter_deserialize(|head| head.get("kind") == Some("Service"))
+    ///     .collect::<Result<_, _>>()
+    ///     .unwrap();
+    /// assert_eq!(services[0].name, "web");
+    /// ```
+    pub fn filter_deserialize<T, F>(self, predicate: F) -> FilterDocuments<'de, T, F>
+    where
+        T: Deserialize<'de>,
+        F: FnMut(&DocumentHead<'de>) -> bool,
+    {
+        FilterDocuments::new(self.into_iter(), predicate)
+    }
+
+    /// Run `check` on every value whose path matches `pattern` before it is deserialized. An
+    /// `Err` message from `check` stops deserialization with a
+    /// [`ValidationFailed`](DeserializeError::ValidationFailed) error at the value's location.
+    /// See [`validate`](crate::validate) for the path syntax.
+    pub fn add_validator<F>(&mut self, pattern: &str, check: F) -> &mut Self
+    where
+        F: Fn(&Node<'_>) -> std::result::Result<(), String> + 'static,
+    {
+        self.validators
+            .push((String::from(pattern), Box::new(check)));
+        self
+    }
+
+    /// Record the span of every value read from now on. See [`Self::source_map`].
+    pub fn enable_source_map(&mut self) -> &mut Self {
+        self.source_map.get_or_insert_with(SourceMap::default);
+        self
+    }
+
+    /// The spans recorded since [`Self::enable_source_map`], or `None` if it wasn't called.
+    pub fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_ref()
+    }
+
+    /// Call `callback` with the path and span of every mapping value and sequence element once
+    /// it has been read, e.g. to record which keys of a config file were used. Paths use the
+    /// syntax described in [`validate`](crate::validate).
+    ///
+    /// ```
+    /// use std::{cell::RefCell, collections::HashMap, rc::Rc};
+    ///
+    /// let read = Rc::new(RefCell::new(Vec::new()));
+    /// let mut de = saphyr_serde::Deserializer::from_str("name: web\nports: [80]\n");
+    /// let paths = Rc::clone(&read);
+    /// de.on_value(move |path, _span| paths.borrow_mut().push(path.to_string()));
+    /// de.start_stream().unwrap();
+    /// de.start_document().unwrap();
+    /// let _: HashMap<String, serde_json::Value> = serde::Deserialize::deserialize(&mut de).unwrap();
+    /// assert_eq!(*read.borrow(), ["name", "ports[0]", "ports"]);
+    /// ```
+    pub fn on_value<F>(&mut self, callback: F) -> &mut Self
+    where
+        F: FnMut(&str, saphyr_parser::Span) + 'static,
+    {
+        self.on_value = Some(Box::new(callback));
+        self
+    }
+
+    /// Whether the mapping and sequence readers need to keep [`Self::path`] up to date.
+    pub(crate) fn tracks_path(&self) -> bool {
+        !self.validators.is_empty() || self.source_map.is_some() || self.on_value.is_some()
+    }
+
+    /// Called once [`Self::path`] points at the next value: records where it starts and runs
+    /// the validators registered for the path against it.
+    pub(crate) fn enter_value(&mut self) -> Result<()> {
+        // A scan error here is reported when the value itself is read.
+        let peeked = peek_parsed(&self.replay, &mut self.yaml);
+        if self.on_value.is_some() {
+            let start = match peeked {
+                Some(Ok((_event, span))) => span.start,
+                _ => self.last_end,
+            };
+            self.value_starts.push(start);
+        }
+        let Some(Ok((event, span))) = peeked else {
+            return Ok(());
+        };
+        if let Some(source_map) = &mut self.source_map {
+            source_map.start(self.path.as_str(), *span);
+        }
+        if self.validators.is_empty() {
+            return Ok(());
+        }
+        let node = Node {
+            path: self.path.as_str(),
+            scalar: match event {
+                Event::Scalar(value, _, _, _) => Some(value),
+                _ => None,
+            },
+            span: *span,
+        };
+        for (pattern, check) in &self.validators {
+            if matches(pattern, node.path) {
+                check(&node).map_err(|message| {
+                    DeserializeError::validation_failed(node.path, message, node.span)
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Called after the value entered with [`Self::enter_value`] has been read.
+    pub(crate) fn leave_value(&mut self) {
+        if let Some(source_map) = &mut self.source_map {
+            source_map.finish(self.path.as_str(), self.last_end);
+        }
+        if let Some(callback) = &mut self.on_value
+            && let Some(start) = self.value_starts.pop()
+        {
+            let span = saphyr_parser::Span {
+                start,
+                end: self.last_end,
+            };
+            callback(self.path.as_str(), span);
+        }
+        self.path.pop();
+    }
+
     pub fn read_boolean(&mut self) -> Result<bool> {
-        let regex_set = self.boolean_re.clone();
+        let schema = self.options.schema;
         let (s, span) = self.read_scalar_string()?;
-        let matches = regex_set.matches(&s.clone());
-        if matches.matched(0) {
-            Ok(true)
-        } else if matches.matched(1) {
-            Ok(false)
-        } else {
-            Err(DeserializeError::not_a_bool(&s, span))
-        }
+        resolve_bool(&s, schema).ok_or_else(|| DeserializeError::not_a_bool(&s, span))
     }
 
     pub fn next_event(&mut self) -> Result<(Event<'de>, saphyr_parser::Span)> {
-        let next = self.yaml.next_event();
-        Ok(next.ok_or(DeserializeError::EarlyTermination)??)
+        // Events read ahead had their limits checked then.
+        let (event, span) = match self.replay.pop_front() {
+            Some(replayed) => replayed,
+            None => {
+                let next = self.yaml.next_event();
+                let (event, span) = next.ok_or(DeserializeError::EarlyTermination)??;
+                self.check_limits(&event, span)?;
+                (event, span)
+            }
+        };
+        self.check_tag(&event, span)?;
+        self.position = span.end;
+        // Block collections end where the next token starts, so their empty end events don't
+        // count here. A flow collection's end event covers its `]` or `}`.
+        if !matches!(event, Event::MappingEnd | Event::SequenceEnd)
+            || span.end.index() > span.start.index()
+        {
+            self.last_end = span.end;
+        }
+        if self.recent_events.len() == RECENT_EVENTS {
+            self.recent_events.pop_front();
+        }
+        self.recent_events.push_back(RecentEvent::new(&event, span));
+        Ok((event, span))
     }
 
-    pub fn peek_event(&mut self) -> Option<&(Event<'_>, saphyr_parser::Span)> {
-        let peek = self.yaml.peek();
-        peek.and_then(|r| r.ok())
+    fn unexpected(
+        &self,
+        event: &Event<'de>,
+        span: saphyr_parser::Span,
+        location: &str,
+    ) -> DeserializeError {
+        // The offending event is the most recent one, so leave it out of the context.
+        let preceding = self.recent_events.len().saturating_sub(1);
+        let recent_events = self
+            .recent_events
+            .iter()
+            .take(preceding)
+            .map(|recent| recent.describe(self.input))
+            .collect();
+        DeserializeError::unexpected(event, span, location, recent_events)
+    }
+
+    /// A [`TypeMismatch`](DeserializeError::TypeMismatch) if `event` starts a node, or an
+    /// unexpected element error otherwise.
+    pub(crate) fn mismatch(
+        &self,
+        expected: &'static str,
+        event: &Event<'de>,
+        span: saphyr_parser::Span,
+        location: &str,
+    ) -> DeserializeError {
+        let found = match event {
+            Event::Scalar(..) => "a scalar",
+            Event::SequenceStart(..) => "a sequence",
+            Event::MappingStart(..) => "a mapping",
+            Event::Alias(_) => "an alias",
+            _ => return self.unexpected(event, span, location),
+        };
+        DeserializeError::type_mismatch(expected, found, span)
+    }
+
+    fn check_tag(&mut self, event: &Event<'de>, span: saphyr_parser::Span) -> Result<()> {
+        let tag = match event {
+            Event::Scalar(_, _, _, tag)
+            | Event::SequenceStart(_, tag)
+            | Event::MappingStart(_, tag) => tag,
+            _ => return Ok(()),
+        };
+        let variant_tag = std::mem::take(&mut self.variant_tag);
+        match tag {
+            Some(tag)
+                if self.options.unknown_tags == UnknownTags::Error
+                    && !variant_tag
+                    && !is_known_tag(tag) =>
+            {
+                Err(DeserializeError::unknown_tag(display_tag(tag), span))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_limits(&mut self, event: &Event<'de>, span: saphyr_parser::Span) -> Result<()> {
+        let options = &self.options;
+        match event {
+            Event::StreamStart => {
+                if let Some(limit) = options.max_input_size
+                    && self.input_len > limit
+                {
+                    return Err(DeserializeError::limit_exceeded(
+                        Limit::InputSize,
+                        limit,
+                        span,
+                    ));
+                }
+            }
+            Event::MappingStart(_, _) | Event::SequenceStart(_, _) => {
+                self.depth += 1;
+                self.nodes += 1;
+                if let Some(limit) = options.max_depth
+                    && self.depth > limit
+                {
+                    return Err(DeserializeError::depth_limit_exceeded(limit, span));
+                }
+            }
+            Event::MappingEnd | Event::SequenceEnd => self.depth = self.depth.saturating_sub(1),
+            Event::Scalar(value, _, _, _) => {
+                self.nodes += 1;
+                if let Some(limit) = options.max_scalar_length
+                    && value.len() > limit
+                {
+                    return Err(DeserializeError::limit_exceeded(
+                        Limit::ScalarLength,
+                        limit,
+                        span,
+                    ));
+                }
+            }
+            Event::Alias(_) => self.nodes += 1,
+            _ => {}
+        }
+        if let Some(limit) = options.max_nodes
+            && self.nodes > limit
+        {
+            return Err(DeserializeError::limit_exceeded(Limit::Nodes, limit, span));
+        }
+        Ok(())
+    }
+
+    pub fn peek_event(&mut self) -> Result<Option<&(Event<'de>, saphyr_parser::Span)>> {
+        match peek_parsed(&self.replay, &mut self.yaml) {
+            Some(Ok(peek)) => Ok(Some(peek)),
+            Some(Err(err)) => Err(err.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses the next event without consuming it, keeping it to be returned again by
+    /// [`Self::peek_event`] and [`Self::next_event`]. Limits are checked as it is parsed.
+    pub(crate) fn read_ahead(&mut self) -> Result<Event<'de>> {
+        let next = self.yaml.next_event();
+        let (event, span) = next.ok_or(DeserializeError::EarlyTermination)??;
+        self.check_limits(&event, span)?;
+        self.replay.push_back((event.clone(), span));
+        Ok(event)
     }
 
     pub fn start_stream(&mut self) -> Result<()> {
         let (next_event, span) = self.next_event()?;
         if !matches!(next_event, saphyr_parser::Event::StreamStart) {
-            Err(DeserializeError::unexpected(
-                &next_event,
-                span,
-                "start_stream",
-            ))
+            Err(self.unexpected(&next_event, span, "start_stream"))
         } else {
             Ok(())
         }
@@ -78,18 +506,58 @@ impl<'de> Deserializer<'de> {
     pub fn end_stream(&mut self) -> Result<()> {
         let (next_event, span) = self.next_event()?;
         if !matches!(next_event, saphyr_parser::Event::StreamEnd) {
-            Err(DeserializeError::unexpected(
-                &next_event,
-                span,
-                "end_stream",
-            ))
+            Err(self.unexpected(&next_event, span, "end_stream"))
         } else {
             Ok(())
         }
     }
 
+    /// Checks that nothing follows the value just read, after `T::deserialize(&mut de)` on a
+    /// deserializer driven by hand. Leftover content, such as a second document, is reported as
+    /// an unexpected element at its span.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    ///
+    /// let mut de = saphyr_serde::Deserializer::from_str("1\n---\n2\n");
+    /// de.start_stream().unwrap();
+    /// de.start_document().unwrap();
+    /// assert_eq!(u8::deserialize(&mut de).unwrap(), 1);
+    /// assert_eq!(de.end().unwrap_err().line(), Some(2));
+    /// ```
+    pub fn end(&mut self) -> Result<()> {
+        if let Some((Event::DocumentEnd, _span)) = self.peek_event()? {
+            self.next_event()?;
+        }
+        self.end_stream()
+    }
+
+    /// After an error partway through a document, skips what is left of it so the next one can
+    /// be read. Document ends only occur at the top level, so no nesting needs tracking.
+    fn skip_to_document_end(&mut self) -> Result<()> {
+        if matches!(
+            self.recent_events.back(),
+            Some(RecentEvent {
+                kind: "DocumentEnd",
+                ..
+            })
+        ) {
+            return Ok(());
+        }
+        loop {
+            match self.next_event()?.0 {
+                Event::DocumentEnd => {
+                    self.depth = 0;
+                    return Ok(());
+                }
+                Event::StreamEnd => return Err(DeserializeError::EarlyTermination),
+                _ => {}
+            }
+        }
+    }
+
     pub fn start_document(&mut self) -> Result<bool> {
-        let peek = self.peek_event();
+        let peek = self.peek_event()?;
         if matches!(peek, Some((saphyr_parser::Event::DocumentStart(_), _))) {
             self.next_event()?;
             Ok(true)
@@ -101,11 +569,7 @@ impl<'de> Deserializer<'de> {
     pub fn end_document(&mut self) -> Result<()> {
         let (next_event, span) = self.next_event()?;
         if !matches!(next_event, saphyr_parser::Event::DocumentEnd) {
-            Err(DeserializeError::unexpected(
-                &next_event,
-                span,
-                "end_document",
-            ))
+            Err(self.unexpected(&next_event, span, "end_document"))
         } else {
             Ok(())
         }
@@ -114,11 +578,7 @@ impl<'de> Deserializer<'de> {
     pub fn start_sequence(&mut self) -> Result<()> {
         let (next_event, span) = self.next_event()?;
         if !matches!(next_event, saphyr_parser::Event::SequenceStart(_, _)) {
-            Err(DeserializeError::unexpected(
-                &next_event,
-                span,
-                "start_sequence",
-            ))
+            Err(self.mismatch("a sequence", &next_event, span, "start_sequence"))
         } else {
             Ok(())
         }
@@ -127,18 +587,14 @@ impl<'de> Deserializer<'de> {
     pub fn end_sequence(&mut self) -> Result<()> {
         let (next_event, span) = self.next_event()?;
         if next_event != saphyr_parser::Event::SequenceEnd {
-            Err(DeserializeError::unexpected(
-                &next_event,
-                span,
-                "end_sequence",
-            ))
+            Err(self.unexpected(&next_event, span, "end_sequence"))
         } else {
             Ok(())
         }
     }
 
     pub fn start_map(&mut self) -> Result<bool> {
-        let peek = self.peek_event();
+        let peek = self.peek_event()?;
         if matches!(
             peek,
             Some((
@@ -156,7 +612,7 @@ impl<'de> Deserializer<'de> {
     pub fn end_map(&mut self) -> Result<()> {
         let (next_event, span) = self.next_event()?;
         if !matches!(next_event, saphyr_parser::Event::MappingEnd,) {
-            Err(DeserializeError::unexpected(&next_event, span, "end_map"))
+            Err(self.unexpected(&next_event, span, "end_map"))
         } else {
             Ok(())
         }
@@ -172,6 +628,38 @@ impl<'de> Deserializer<'de> {
         Ok(())
     }
 
+    /// Skips the next node, a scalar, alias or whole sequence or mapping, and returns its span.
+    /// Aliases aren't followed, so skipping one is as cheap as skipping a scalar.
+    ///
+    /// ```
+    /// let mut de = saphyr_serde::Deserializer::from_str("[{a: 1}, [2, 3]]");
+    /// de.start_stream().unwrap();
+    /// de.start_document().unwrap();
+    /// de.start_sequence().unwrap();
+    /// let span = de.skip_value().unwrap();
+    /// assert_eq!((span.start.index(), span.end.index()), (1, 7));
+    /// ```
+    pub fn skip_value(&mut self) -> Result<saphyr_parser::Span> {
+        let (event, span) = self.next_event()?;
+        match event {
+            Event::Scalar(..) | Event::Alias(_) => return Ok(span),
+            Event::SequenceStart(..) | Event::MappingStart(..) => {}
+            event => return Err(self.unexpected(&event, span, "skip_value")),
+        }
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next_event()?.0 {
+                Event::SequenceStart(..) | Event::MappingStart(..) => depth += 1,
+                Event::SequenceEnd | Event::MappingEnd => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(saphyr_parser::Span {
+            start: span.start,
+            end: self.last_end,
+        })
+    }
+
     pub fn parse_scalar<T>(&mut self, type_string: &str) -> Result<T>
     where
         T: FromStr,
@@ -184,29 +672,232 @@ impl<'de> Deserializer<'de> {
         })
     }
 
-    pub fn read_scalar_string(
-        &mut self,
-    ) -> Result<(std::borrow::Cow<'_, str>, saphyr_parser::Span)> {
-        match self.next_event()? {
-            (saphyr_parser::Event::Scalar(s, _, _, _), span) => Ok((s, span)),
-            (event, span) => Err(DeserializeError::unexpected(
-                &event,
+    pub fn parse_integer<T>(&mut self, type_string: &str) -> Result<T>
+    where
+        T: TryFrom<i128>,
+    {
+        let schema = self.options.schema;
+        let (s, span) = self.read_scalar_string()?;
+        parse_int(&s, schema).or_else(|e| {
+            let whole = self
+                .options
+                .whole_floats_as_integers
+                .then(|| resolve_float(&s, schema))
+                .flatten();
+            let message = match whole {
+                Some(value) if value.is_finite() && value.fract() == 0.0 => {
+                    // Checked against i128 first so huge floats don't saturate into range.
+                    if let Some(value) = (value.abs() < 2f64.powi(127))
+                        .then(|| T::try_from(value as i128).ok())
+                        .flatten()
+                    {
+                        return Ok(value);
+                    }
+                    "number out of range for target type".to_string()
+                }
+                Some(_) => "not a whole number".to_string(),
+                None => e.to_string(),
+            };
+            Err(DeserializeError::number_parse_failure(
+                &s,
                 span,
-                "deserialize_str",
+                type_string,
+                &message,
+            ))
+        })
+    }
+
+    pub fn parse_float<T>(&mut self, type_string: &str, from_f64: fn(f64) -> T) -> Result<T>
+    where
+        T: FromStr,
+    {
+        let schema = self.options.schema;
+        let (s, span) = self.read_scalar_string()?;
+        match resolve_float(&s, schema) {
+            // Plain decimals are parsed as `T` directly, so an `f32` is only rounded once.
+            Some(value) => Ok(s.parse().unwrap_or_else(|_| from_f64(value))),
+            None => Err(DeserializeError::number_parse_failure(
+                &s,
+                span,
+                type_string,
+                "invalid float literal",
             )),
         }
     }
 
-    pub fn peek_scalar_string(
-        &mut self,
-    ) -> Option<(std::borrow::Cow<'_, str>, saphyr_parser::Span)> {
-        match self.peek_event()? {
-            (saphyr_parser::Event::Scalar(s, _, _, _), span) => Some((s.clone(), span.to_owned())),
+    pub fn read_scalar_string(&mut self) -> Result<(Cow<'de, str>, saphyr_parser::Span)> {
+        match self.next_event()? {
+            (saphyr_parser::Event::Scalar(s, _, _, _), span) => Ok((s, span)),
+            (event, span) => Err(self.mismatch("a scalar", &event, span, "deserialize_str")),
+        }
+    }
+
+    /// Whether the next node is a null scalar. One tagged `!!str` is a string whatever it says.
+    pub(crate) fn peek_null(&mut self) -> bool {
+        match self.peek_event() {
+            Ok(Some((Event::Scalar(s, style, _, tag), _span))) => {
+                !tag.as_ref().is_some_and(|tag| is_core_tag(tag, "str"))
+                    && is_null_scalar(s, *style)
+            }
+            _ => false,
+        }
+    }
+
+    /// The text of the next node, if it is a scalar.
+    pub(crate) fn peek_scalar(&mut self) -> Option<Cow<'de, str>> {
+        match self.peek_event().ok()?? {
+            (saphyr_parser::Event::Scalar(s, _, _, _), _span) => Some(s.clone()),
             _ => None,
         }
     }
+
+    pub fn peek_scalar_string(&mut self) -> Option<(&str, saphyr_parser::Span)> {
+        match self.peek_event().ok()?? {
+            (saphyr_parser::Event::Scalar(s, _, _, _), span) => Some((s, *span)),
+            _ => None,
+        }
+    }
+
+    /// The tag on the next node, if it has one.
+    fn peek_tag(&mut self) -> Option<&saphyr_parser::Tag> {
+        match self.peek_event().ok()?? {
+            (saphyr_parser::Event::Scalar(_, _, _, tag), _)
+            | (saphyr_parser::Event::SequenceStart(_, tag), _)
+            | (saphyr_parser::Event::MappingStart(_, tag), _) => tag.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The name in the local tag on the next node, if it has one.
+    fn peek_local_tag(&mut self) -> Option<String> {
+        local_tag(self.peek_tag()?).map(String::from)
+    }
+
+    /// The long form key for the next node when it carries a CloudFormation intrinsic function
+    /// tag that hasn't been expanded yet.
+    fn peek_intrinsic(&mut self) -> Option<String> {
+        if !self.options.cloudformation_tags || self.variant_tag {
+            return None;
+        }
+        let key = long_form(&self.peek_local_tag()?)?;
+        self.variant_tag = true;
+        Some(key)
+    }
+}
+
+/// The next event, from those read ahead if there are any, or else from the parser.
+fn peek_parsed<'a, 'de>(
+    replay: &'a VecDeque<(Event<'de>, saphyr_parser::Span)>,
+    yaml: &'a mut saphyr_parser::Parser<'de, saphyr_parser::StrInput<'de>>,
+) -> Option<std::result::Result<&'a (Event<'de>, saphyr_parser::Span), saphyr_parser::ScanError>> {
+    match replay.front() {
+        Some(replayed) => Some(Ok(replayed)),
+        None => yaml.peek(),
+    }
+}
+
+/// Stack headroom below which [`nested`] switches to a new stack segment.
+#[cfg(feature = "stacker")]
+const STACK_RED_ZONE: usize = 64 * 1024;
+/// The size of each stack segment [`nested`] allocates.
+#[cfg(feature = "stacker")]
+const STACK_SEGMENT: usize = 1024 * 1024;
+
+/// Runs `f`, which reads one level deeper into the document. With the `stacker` feature the
+/// stack grows on demand here, so nesting is bounded by memory instead of the thread's stack.
+#[cfg(feature = "stacker")]
+pub(crate) fn nested<R>(f: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, f)
+}
+
+#[cfg(not(feature = "stacker"))]
+pub(crate) fn nested<R>(f: impl FnOnce() -> R) -> R {
+    f()
 }
 
+/// An iterator over the documents of a stream, created by [`Deserializer::into_iter`]. It stops
+/// after the first error.
+pub struct StreamDeserializer<'de, T> {
+    de: Deserializer<'de>,
+    started: bool,
+    finished: bool,
+    output: PhantomData<T>,
+}
+
+impl<'de, T> StreamDeserializer<'de, T>
+where
+    T: Deserialize<'de>,
+{
+    /// See [`Deserializer::byte_offset`].
+    pub fn byte_offset(&self) -> usize {
+        self.de.byte_offset()
+    }
+
+    /// See [`Deserializer::position`].
+    pub fn position(&self) -> saphyr_parser::Marker {
+        self.de.position()
+    }
+
+    /// Moves into the next document, or reads the end of the stream and returns `false`.
+    fn start_next_document(&mut self) -> Result<bool> {
+        if !self.started {
+            self.started = true;
+            self.de.start_stream()?;
+        }
+        if !self.de.start_document()? {
+            self.de.end_stream()?;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn next_document(&mut self) -> Result<Option<T>> {
+        if !self.start_next_document()? {
+            return Ok(None);
+        }
+        let t = T::deserialize(&mut self.de)?;
+        self.de.end_document()?;
+        Ok(Some(t))
+    }
+
+    /// Reads the next document as a `T`, or skips over it when `keep` rejects its head. Returns
+    /// `None` at the end of the stream.
+    pub(crate) fn next_document_where<F>(&mut self, keep: F) -> Result<Option<Option<T>>>
+    where
+        F: FnOnce(&DocumentHead<'de>) -> bool,
+    {
+        if !self.start_next_document()? {
+            return Ok(None);
+        }
+        let t = if keep(&DocumentHead::scan(&mut self.de)?) {
+            Some(T::deserialize(&mut self.de)?)
+        } else {
+            IgnoredAny::deserialize(&mut self.de)?;
+            None
+        };
+        self.de.end_document()?;
+        Ok(Some(t))
+    }
+}
+
+impl<'de, T> Iterator for StreamDeserializer<'de, T>
+where
+    T: Deserialize<'de>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let next = self.next_document().transpose();
+        self.finished = !matches!(next, Some(Ok(_)));
+        next
+    }
+}
+
+impl<'de, T> std::iter::FusedIterator for StreamDeserializer<'de, T> where T: Deserialize<'de> {}
+
 impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
     type Error = crate::error::DeserializeError;
 
@@ -214,15 +905,35 @@ impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        // An empty document, such as one holding only `...`, is null.
+        if let Some((Event::DocumentEnd | Event::StreamEnd, _span)) = self.peek_event()? {
+            return visitor.visit_unit();
+        }
+        if let Some(key) = self.peek_intrinsic() {
+            return visitor.visit_map(Intrinsic::new(self, key));
+        }
         match self.next_event()? {
-            (saphyr_parser::Event::Scalar(value, _, _, _), _span) => {
-                // TODO: have to detect and parse the string as a particular type
-                // 'n' => self.deserialize_unit(visitor),
-                // 't' | 'f' => self.deserialize_bool(visitor),
-                // '"' => self.deserialize_str(visitor),
-                // '0'..='9' => self.deserialize_u64(visitor),
-                // '-' => self.deserialize_i64(visitor),
-                visitor.visit_str(&value)
+            (saphyr_parser::Event::Scalar(value, style, _, tag), _span) => {
+                // Only plain scalars are resolved; quoted and block scalars, and anything
+                // tagged `!!str`, are always strings.
+                let is_str = tag.as_ref().is_some_and(|tag| is_core_tag(tag, "str"));
+                if style != ScalarStyle::Plain || is_str {
+                    return visit_cow_str(value, visitor);
+                }
+                let schema = self.options.schema;
+                if is_null_scalar(&value, style) {
+                    visitor.visit_unit()
+                } else if let Some(b) = resolve_bool(&value, schema) {
+                    visitor.visit_bool(b)
+                } else if let Ok(n) = parse_int::<i64>(&value, schema) {
+                    visitor.visit_i64(n)
+                } else if let Ok(n) = parse_int::<u64>(&value, schema) {
+                    visitor.visit_u64(n)
+                } else if let Some(f) = resolve_float(&value, schema) {
+                    visitor.visit_f64(f)
+                } else {
+                    visit_cow_str(value, visitor)
+                }
             }
             (saphyr_parser::Event::MappingStart(_map, _), _span) => {
                 let result = visitor.visit_map(YamlMapping::new(self));
@@ -234,11 +945,7 @@ impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
                 self.end_sequence()?;
                 result
             }
-            (event, span) => Err(DeserializeError::unexpected(
-                &event,
-                span,
-                "deserialize_any",
-            )),
+            (event, span) => Err(self.unexpected(&event, span, "deserialize_any")),
         }
     }
 
@@ -260,70 +967,70 @@ impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i8(self.parse_scalar("i8")?)
+        visitor.visit_i8(self.parse_integer("i8")?)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i16(self.parse_scalar("i16")?)
+        visitor.visit_i16(self.parse_integer("i16")?)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i32(self.parse_scalar("i32")?)
+        visitor.visit_i32(self.parse_integer("i32")?)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i64(self.parse_scalar("i64")?)
+        visitor.visit_i64(self.parse_integer("i64")?)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u8(self.parse_scalar("u8")?)
+        visitor.visit_u8(self.parse_integer("u8")?)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u16(self.parse_scalar("u16")?)
+        visitor.visit_u16(self.parse_integer("u16")?)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u32(self.parse_scalar("u32")?)
+        visitor.visit_u32(self.parse_integer("u32")?)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u64(self.parse_scalar("u64")?)
+        visitor.visit_u64(self.parse_integer("u64")?)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_f32(self.parse_scalar("f32")?)
+        visitor.visit_f32(self.parse_float("f32", |v| v as f32)?)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_f64(self.parse_scalar("f64")?)
+        visitor.visit_f64(self.parse_float("f64", |v| v)?)
     }
 
     fn deserialize_char<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
@@ -338,7 +1045,7 @@ impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
         V: Visitor<'de>,
     {
         let (s, _span) = self.read_scalar_string()?;
-        visitor.visit_str(&s)
+        visit_cow_str(s, visitor)
     }
 
     fn deserialize_string<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
@@ -346,41 +1053,47 @@ impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
         V: Visitor<'de>,
     {
         let (s, _span) = self.read_scalar_string()?;
-        visitor.visit_str(&s)
+        visit_cow_str(s, visitor)
     }
 
-    fn deserialize_bytes<V>(self, _visitor: V) -> std::result::Result<V::Value, Self::Error>
+    /// Reads a `!!binary` scalar as base64, any other scalar as its UTF-8 bytes and a sequence
+    /// as a list of byte values. The parser hands scalars out owned, so read into an owned
+    /// buffer such as `Vec<u8>` or `serde_bytes::ByteBuf` rather than `&[u8]`.
+    fn deserialize_bytes<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!("deserialize an decode a base64 string")
+        if let Some((Event::SequenceStart(_, _), _span)) = self.peek_event()? {
+            return self.deserialize_seq(visitor);
+        }
+        match self.next_event()? {
+            (Event::Scalar(value, _, _, Some(tag)), span) if is_core_tag(&tag, "binary") => {
+                let bytes = decode_base64(&value).ok_or(DeserializeError::invalid_binary(span))?;
+                visitor.visit_byte_buf(bytes)
+            }
+            (Event::Scalar(value, _, _, _), _span) => {
+                visitor.visit_byte_buf(value.into_owned().into_bytes())
+            }
+            (event, span) => Err(self.unexpected(&event, span, "deserialize_bytes")),
+        }
     }
 
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> std::result::Result<V::Value, Self::Error>
+    fn deserialize_byte_buf<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!("deserialize an decode a base64 string")
+        self.deserialize_bytes(visitor)
     }
 
     fn deserialize_option<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let null_regex = self.null_re.clone();
-        match self
-            .peek_scalar_string()
-            .map(|(s, _span)| null_regex.is_match(&s))
-        {
-            Some(true) => {
-                self.next_event()?;
-                visitor.visit_none()
-            }
-            _ => visitor.visit_some(self), // Some(false) => visitor.visit_some(self),
-                                           // None => {
-                                           //     // self.next_event()?;
-                                           //     visitor.visit_none()
-                                           // }
+        if self.peek_null() {
+            self.next_event()?;
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
         }
     }
 
@@ -388,16 +1101,11 @@ impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let null_regex = self.null_re.clone();
-        match self
-            .peek_scalar_string()
-            .map(|(s, _span)| null_regex.is_match(&s))
-        {
-            Some(true) => {
-                self.next_event()?;
-                visitor.visit_unit()
-            }
-            _ => Err(DeserializeError::TypeError),
+        if self.peek_null() {
+            self.next_event()?;
+            visitor.visit_unit()
+        } else {
+            Err(DeserializeError::TypeError)
         }
     }
 
@@ -414,12 +1122,27 @@ impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
 
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        if name == TAGGED {
+            let Some(tag) = self.peek_tag().map(display_tag) else {
+                let (event, span) = self.next_event()?;
+                return Err(self.mismatch("a tagged node", &event, span, "Tagged"));
+            };
+            self.variant_tag = true;
+            return visitor.visit_enum(Enum::tagged(self, &[], tag));
+        }
+        if name == TEMPLATE_OR
+            && self.options.template_expressions
+            && self.peek_scalar().is_some_and(|s| is_template(&s))
+        {
+            let (template, _span) = self.read_scalar_string()?;
+            return visitor.visit_str(&template);
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -427,6 +1150,14 @@ impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        if let Some((Event::MappingStart(_, Some(tag)), _span)) = self.peek_event()?
+            && is_core_tag(tag, "set")
+        {
+            self.next_event()?;
+            let value = visitor.visit_seq(YamlSet::new(self))?;
+            self.end_map()?;
+            return Ok(value);
+        }
         self.start_sequence()?;
         let value = visitor.visit_seq(YamlSequence::new(self))?;
         self.end_sequence()?;
@@ -435,13 +1166,28 @@ impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
 
     fn deserialize_tuple<V>(
         self,
-        _len: usize,
+        len: usize,
         visitor: V,
     ) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        let span = self.peek_event()?.map(|(_event, span)| *span);
+        self.start_sequence()?;
+        let mut seq = YamlSequence::new(self);
+        let value = visitor
+            .visit_seq(&mut seq)
+            .map_err(|err| err.at_node(span))?;
+        let extra = seq.skip_rest()?;
+        if extra > 0 {
+            return Err(DeserializeError::invalid_length(
+                len + extra,
+                format!("a sequence of length {len}"),
+                span,
+            ));
+        }
+        self.end_sequence()?;
+        Ok(value)
     }
 
     fn deserialize_tuple_struct<V>(
@@ -460,13 +1206,26 @@ impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        if let Some(key) = self.peek_intrinsic() {
+            return visitor.visit_map(Intrinsic::new(self, key));
+        }
         if self.start_map()? {
             let value = visitor.visit_map(YamlMapping::new(self))?;
             self.end_map()?;
-            Ok(value)
-        } else {
-            visitor.visit_map(YamlMapping::empty(self))
+            return Ok(value);
         }
+        match self.peek_event()? {
+            // An empty value, like an empty document, reads as an empty mapping.
+            Some((Event::Scalar(s, style, _, _), _span)) if is_null_scalar(s, *style) => {
+                self.next_event()?;
+            }
+            Some((Event::Scalar(..) | Event::SequenceStart(..) | Event::Alias(_), _span)) => {
+                let (event, span) = self.next_event()?;
+                return Err(self.mismatch("a mapping", &event, span, "deserialize_map"));
+            }
+            _ => {}
+        }
+        visitor.visit_map(YamlMapping::empty(self))
     }
 
     fn deserialize_struct<V>(
@@ -484,28 +1243,38 @@ impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> std::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        if self.options.tagged_enums
+            && let Some(name) = self.peek_local_tag()
+        {
+            self.variant_tag = true;
+            return visitor.visit_enum(Enum::tagged(self, variants, name));
+        }
         match self.next_event()? {
-            (saphyr_parser::Event::Scalar(key, _, _, _), _span) => {
-                let s = key.to_string();
-                visitor.visit_enum(s.into_deserializer())
+            (saphyr_parser::Event::Scalar(key, _, _, _), span) => {
+                let key = if self.options.case_insensitive_enums {
+                    match_variant(&key, variants).map_or(key, Cow::Borrowed)
+                } else {
+                    key
+                };
+                visitor
+                    .visit_enum(key.into_deserializer())
+                    .map_err(|err: DeserializeError| err.at_node(Some(span)))
             }
             (saphyr_parser::Event::MappingStart(_, _), _span) => {
-                let value = visitor.visit_enum(Enum::new(self))?;
+                let value = visitor.visit_enum(Enum::new(self, variants))?;
                 self.end_map()?;
                 Ok(value)
             }
 
-            (event, span) => Err(DeserializeError::unexpected(
-                &event,
-                span,
-                "deserialize_enum",
-            )),
+            (event, span) => {
+                Err(self.mismatch("an enum variant", &event, span, "deserialize_enum"))
+            }
         }
     }
 
@@ -514,22 +1283,424 @@ impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
         V: Visitor<'de>,
     {
         let (s, _span) = self.read_scalar_string()?;
-        visitor.visit_str(&s)
+        visit_cow_str(s, visitor)
     }
 }
 
-#[allow(dead_code)]
+/// Hands serde a scalar's text. The parser hands scalars out owned, so the text is moved into
+/// `visit_string` rather than copied; nothing is borrowed from the input.
+fn visit_cow_str<'de, V>(s: Cow<'de, str>, visitor: V) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    visitor.visit_string(s.into_owned())
+}
+
 pub fn from_str<'a, T>(s: &'a str) -> Result<T>
 where
     T: Deserialize<'a>,
 {
-    let mut deserializer = Deserializer::from_str(s);
+    from_str_with_options(s, DeserializerOptions::default())
+}
+
+pub fn from_str_with_options<'a, T>(s: &'a str, options: DeserializerOptions) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    from_str_seed_with_options(PhantomData, s, options)
+}
+
+/// Deserializes through `seed`, for values that need context from outside the input, such as
+/// an interner or a schema registry.
+///
+/// ```
+/// use serde::de::{DeserializeSeed, Deserializer};
+///
+/// struct Scaled(u32);
+///
+/// impl<'de> DeserializeSeed<'de> for Scaled {
+///     type Value = u32;
+///
+///     fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<u32, D::Error> {
+///         Ok(self.0 * <u32 as serde::Deserialize>::deserialize(deserializer)?)
+///     }
+/// }
+///
+/// assert_eq!(saphyr_serde::from_str_seed(Scaled(1000), "3").unwrap(), 3000);
+/// ```
+pub fn from_str_seed<'a, S>(seed: S, s: &'a str) -> Result<S::Value>
+where
+    S: DeserializeSeed<'a>,
+{
+    from_str_seed_with_options(seed, s, DeserializerOptions::default())
+}
+
+pub fn from_str_seed_with_options<'a, S>(
+    seed: S,
+    s: &'a str,
+    options: DeserializerOptions,
+) -> Result<S::Value>
+where
+    S: DeserializeSeed<'a>,
+{
+    let mut deserializer = Deserializer::from_str_with_options(s, options);
+    deserializer.start_stream()?;
+    deserializer.start_document()?;
+    let value = seed.deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// Takes ownership of `input`, such as an HTTP body or the value of an environment variable,
+/// so the caller doesn't have to keep it alive while deserializing a `T` that owns its data.
+pub fn from_string<S, T>(input: S) -> Result<T>
+where
+    S: Into<String>,
+    T: DeserializeOwned,
+{
+    from_string_with_options(input, DeserializerOptions::default())
+}
+
+pub fn from_string_with_options<S, T>(input: S, options: DeserializerOptions) -> Result<T>
+where
+    S: Into<String>,
+    T: DeserializeOwned,
+{
+    from_str_with_options(&input.into(), options)
+}
+
+/// Deserializes every document in the stream as a `T`. An empty stream gives an empty `Vec`.
+///
+/// ```
+/// let ids: Vec<u32> = saphyr_serde::from_str_all("1\n---\n2\n---\n3\n").unwrap();
+/// assert_eq!(ids, [1, 2, 3]);
+/// ```
+pub fn from_str_all<'a, T>(s: &'a str) -> Result<Vec<T>>
+where
+    T: Deserialize<'a>,
+{
+    from_str_all_with_options(s, DeserializerOptions::default())
+}
+
+pub fn from_str_all_with_options<'a, T>(s: &'a str, options: DeserializerOptions) -> Result<Vec<T>>
+where
+    T: Deserialize<'a>,
+{
+    Deserializer::from_str_with_options(s, options)
+        .into_iter()
+        .collect()
+}
+
+/// Checks that every document in `s` deserializes as a `T`, without keeping the values. Unlike
+/// [`from_str_all`] it carries on after a document fails, so one run reports a problem in each
+/// document rather than only the first. A syntax error ends the check, as the parser can't
+/// recover from it.
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct Service {
+///     #[allow(dead_code)]
+///     port: u16,
+/// }
+///
+/// let errors = saphyr_serde::check::<Service>("port: 80\n---\nport: x\n---\nname: web\n")
+///     .unwrap_err();
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!(errors[0].line(), Some(3));
+/// ```
+pub fn check<'a, T>(s: &'a str) -> std::result::Result<(), Vec<DeserializeError>>
+where
+    T: Deserialize<'a>,
+{
+    check_with_options::<T>(s, DeserializerOptions::default())
+}
+
+pub fn check_with_options<'a, T>(
+    s: &'a str,
+    options: DeserializerOptions,
+) -> std::result::Result<(), Vec<DeserializeError>>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::from_str_with_options(s, options);
+    let mut errors = Vec::new();
+    if let Err(err) = deserializer.start_stream() {
+        return Err(vec![err]);
+    }
+    loop {
+        match deserializer.start_document() {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(err) => {
+                errors.push(err);
+                break;
+            }
+        }
+        let Err(err) = T::deserialize(&mut deserializer)
+            .map(drop)
+            .and_then(|()| deserializer.end_document())
+        else {
+            continue;
+        };
+        let fatal = matches!(err, DeserializeError::ScanError(_));
+        errors.push(err);
+        if fatal || deserializer.skip_to_document_end().is_err() {
+            break;
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Like [`from_str`], but also returns the span of every value that was read, so that problems
+/// found after deserialization can still point at the source.
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// let (config, source_map) =
+///     saphyr_serde::from_str_with_source_map::<Config>("port: 80\n").unwrap();
+/// assert_eq!(config.port, 80);
+/// assert_eq!(source_map.get("port").unwrap().start.col(), 6);
+/// ```
+pub fn from_str_with_source_map<'a, T>(s: &'a str) -> Result<(T, SourceMap)>
+where
+    T: Deserialize<'a>,
+{
+    from_str_with_source_map_with_options(s, DeserializerOptions::default())
+}
+
+pub fn from_str_with_source_map_with_options<'a, T>(
+    s: &'a str,
+    options: DeserializerOptions,
+) -> Result<(T, SourceMap)>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::from_str_with_options(s, options);
+    deserializer.enable_source_map();
     deserializer.start_stream()?;
-    let has_document = deserializer.start_document()?;
+    deserializer.start_document()?;
     let t = T::deserialize(&mut deserializer)?;
-    if has_document {
-        deserializer.end_document()?;
+    deserializer.end()?;
+    let source_map = deserializer.source_map.take().unwrap_or_default();
+    Ok((t, source_map))
+}
+
+/// Deserializes UTF-8 encoded input in place. UTF-16 and UTF-32 input is rejected with an
+/// [`InvalidData`](std::io::ErrorKind::InvalidData) IO error, as it would have to be transcoded
+/// first; [`from_slice_owned`] does that.
+pub fn from_slice<'a, T>(v: &'a [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    from_slice_with_options(v, DeserializerOptions::default())
+}
+
+pub fn from_slice_with_options<'a, T>(v: &'a [u8], options: DeserializerOptions) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    if options.lossy_utf8 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "lossy_utf8 needs a repaired copy of the input, read it with from_slice_owned",
+        )
+        .into());
+    }
+    let encoding = encoding::detect(v);
+    if encoding != Encoding::Utf8 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("{encoding} input can't be borrowed from, read it with from_slice_owned"),
+        )
+        .into());
+    }
+    let s = std::str::from_utf8(v)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+    from_str_with_options(s, options)
+}
+
+/// Deserializes input in any of the encodings YAML allows into a `T` that doesn't borrow from
+/// it. UTF-16 and UTF-32 input, detected from a byte order mark or the position of the zero
+/// bytes, is transcoded to UTF-8 first, as [`from_reader`] does.
+pub fn from_slice_owned<T>(v: &[u8]) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    from_slice_owned_with_options(v, DeserializerOptions::default())
+}
+
+pub fn from_slice_owned_with_options<T>(v: &[u8], options: DeserializerOptions) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    from_reader_with_options(v, options)
+}
+
+/// Reads all of `reader` and deserializes it. UTF-16 and UTF-32 input, detected from a byte
+/// order mark or the position of the zero bytes, is transcoded to UTF-8 first, so spans in
+/// errors count characters of the transcoded text.
+pub fn from_reader<R, T>(reader: R) -> Result<T>
+where
+    R: std::io::Read,
+    T: DeserializeOwned,
+{
+    from_reader_with_options(reader, DeserializerOptions::default())
+}
+
+pub fn from_reader_with_options<R, T>(reader: R, options: DeserializerOptions) -> Result<T>
+where
+    R: std::io::Read,
+    T: DeserializeOwned,
+{
+    let input = read_input(reader, &options)?;
+    from_input(input, options)
+}
+
+/// Deserializes input read in full by [`from_reader`] or [`from_async_reader`], decompressing
+/// and transcoding it as needed.
+fn from_input<T>(input: Vec<u8>, options: DeserializerOptions) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    #[cfg(feature = "flate2")]
+    if input.starts_with(&GZIP_MAGIC) {
+        return from_gzip_reader_with_options(input.as_slice(), options);
+    }
+    let input = decode(input, &options)?;
+    from_str_with_options(&input, options)
+}
+
+/// Reads all of `reader`, but no more than [`max_input_size`](DeserializerOptions::max_input_size)
+/// bytes, so an endless or decompressing stream can't exhaust memory before the limit applies.
+fn read_input<R>(reader: R, options: &DeserializerOptions) -> Result<Vec<u8>>
+where
+    R: std::io::Read,
+{
+    use std::io::Read;
+
+    let mut input = Vec::new();
+    let Some(limit) = options.max_input_size else {
+        let mut reader = reader;
+        reader.read_to_end(&mut input)?;
+        return Ok(input);
+    };
+    reader.take(limit as u64 + 1).read_to_end(&mut input)?;
+    if input.len() > limit {
+        return Err(input_too_large(limit));
+    }
+    Ok(input)
+}
+
+fn input_too_large(limit: usize) -> DeserializeError {
+    let start = saphyr_parser::Marker::new(0, 1, 0);
+    DeserializeError::limit_exceeded(Limit::InputSize, limit, saphyr_parser::Span::empty(start))
+}
+
+/// Transcodes `input` to UTF-8, reporting any repairs made under
+/// [`lossy_utf8`](DeserializerOptions::lossy_utf8) as a warning.
+fn decode(input: Vec<u8>, options: &DeserializerOptions) -> Result<String> {
+    let (input, replacements) = encoding::decode(input, options.lossy_utf8)?;
+    if replacements > 0
+        && let Some(warnings) = &options.warnings
+    {
+        warnings.push(Warning::ReplacedInvalidEncoding { replacements });
+    }
+    Ok(input)
+}
+
+/// Reads the file at `path` with [`from_reader`]. Errors, including failing to open the file,
+/// are wrapped in [`InFile`](DeserializeError::InFile) to say which file was at fault.
+pub fn from_file<P, T>(path: P) -> Result<T>
+where
+    P: AsRef<std::path::Path>,
+    T: DeserializeOwned,
+{
+    from_file_with_options(path, DeserializerOptions::default())
+}
+
+pub fn from_file_with_options<P, T>(path: P, options: DeserializerOptions) -> Result<T>
+where
+    P: AsRef<std::path::Path>,
+    T: DeserializeOwned,
+{
+    let path = path.as_ref();
+    std::fs::File::open(path)
+        .map_err(DeserializeError::from)
+        .and_then(|file| from_reader_with_options(file, options))
+        .map_err(|error| DeserializeError::InFile {
+            path: path.to_path_buf(),
+            error: Box::new(error),
+        })
+}
+
+/// The first bytes of every gzip stream, which [`from_reader`] looks for.
+#[cfg(feature = "flate2")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Decompresses gzipped YAML from `reader` and deserializes it. [`from_reader`] does this by
+/// itself for input starting with the gzip magic bytes.
+#[cfg(feature = "flate2")]
+pub fn from_gzip_reader<R, T>(reader: R) -> Result<T>
+where
+    R: std::io::Read,
+    T: DeserializeOwned,
+{
+    from_gzip_reader_with_options(reader, DeserializerOptions::default())
+}
+
+#[cfg(feature = "flate2")]
+pub fn from_gzip_reader_with_options<R, T>(reader: R, options: DeserializerOptions) -> Result<T>
+where
+    R: std::io::Read,
+    T: DeserializeOwned,
+{
+    let input = read_input(flate2::read::GzDecoder::new(reader), &options)?;
+    let input = decode(input, &options)?;
+    from_str_with_options(&input, options)
+}
+
+/// Reads all of `reader` without blocking the executor, then deserializes it as
+/// [`from_reader`] does. The input is buffered in full before parsing starts.
+#[cfg(feature = "tokio")]
+pub async fn from_async_reader<R, T>(reader: R) -> Result<T>
+where
+    R: tokio::io::AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    from_async_reader_with_options(reader, DeserializerOptions::default()).await
+}
+
+#[cfg(feature = "tokio")]
+pub async fn from_async_reader_with_options<R, T>(
+    mut reader: R,
+    options: DeserializerOptions,
+) -> Result<T>
+where
+    R: tokio::io::AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    use tokio::io::AsyncReadExt;
+
+    let mut input = Vec::new();
+    match options.max_input_size {
+        Some(limit) => {
+            let mut reader = reader.take(limit as u64 + 1);
+            reader.read_to_end(&mut input).await?;
+            if input.len() > limit {
+                return Err(input_too_large(limit));
+            }
+        }
+        None => {
+            reader.read_to_end(&mut input).await?;
+        }
     }
-    deserializer.end_stream()?;
-    Ok(t)
+    from_input(input, options)
 }
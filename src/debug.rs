@@ -0,0 +1,53 @@
+//! What the parser makes of an input, for bug reports and for working out why a type doesn't
+//! match a document.
+
+use std::fmt::{self, Display};
+
+use saphyr_parser::{Event, Parser, Span};
+
+use crate::error::Result;
+
+/// Every event the parser produces for `input`, in order, with its span.
+///
+/// ```
+/// use saphyr_parser::Event;
+///
+/// let events = saphyr_serde::debug::events("a: 1").unwrap();
+/// assert!(matches!(events[3].0, Event::Scalar(ref key, ..) if key == "a"));
+/// ```
+pub fn events(input: &str) -> Result<Vec<(Event<'_>, Span)>> {
+    let mut parser = Parser::new_from_str(input);
+    let mut events = Vec::new();
+    while let Some(next) = parser.next_event() {
+        events.push(next?);
+    }
+    Ok(events)
+}
+
+/// Lists the events for `input` one per line, prefixed with their line and column. Formats a
+/// scan error as the last line rather than failing.
+///
+/// ```
+/// println!("{}", saphyr_serde::debug::dump("a: [1, 2]"));
+/// ```
+pub fn dump(input: &str) -> Dump<'_> {
+    Dump(input)
+}
+
+/// The output of [`dump`].
+pub struct Dump<'a>(&'a str);
+
+impl Display for Dump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parser = Parser::new_from_str(self.0);
+        while let Some(next) = parser.next_event() {
+            match next {
+                Ok((event, span)) => {
+                    writeln!(f, "{}:{}\t{:?}", span.start.line(), span.start.col(), event)?
+                }
+                Err(err) => return writeln!(f, "error: {err}"),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt::{self, Display};
+
+use miette::{Diagnostic, LabeledSpan, SourceCode};
+
+use crate::{error::DeserializeError, position::byte_offset};
+
+// Labels need byte offsets into the source, which the error alone can't give, so they come from
+// `SourceDiagnostic`.
+impl Diagnostic for DeserializeError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let code = match self {
+            Self::TrailingCharacters => "saphyr_serde::trailing_characters",
+            Self::TypeError => "saphyr_serde::type_error",
+            Self::UnexpectedElement { .. } => "saphyr_serde::unexpected_element",
+            Self::SerdeError(_) => "saphyr_serde::serde",
+            Self::NumberParseError { .. } => "saphyr_serde::number",
+            Self::BoolParseError { .. } => "saphyr_serde::boolean",
+            Self::BinaryParseError { .. } => "saphyr_serde::binary",
+            Self::TypeMismatch { .. } => "saphyr_serde::type_mismatch",
+            Self::InvalidLength { .. } => "saphyr_serde::length",
+            Self::DuplicateKey { .. } => "saphyr_serde::duplicate_key",
+            Self::DepthLimitExceeded { .. } => "saphyr_serde::depth_limit",
+            Self::LimitExceeded { .. } => "saphyr_serde::limit",
+            Self::UnknownField { .. } => "saphyr_serde::unknown_field",
+            Self::UnknownVariant { .. } => "saphyr_serde::unknown_variant",
+            Self::UnknownTag { .. } => "saphyr_serde::unknown_tag",
+            Self::ValidationFailed { .. } => "saphyr_serde::validation",
+            Self::EarlyTermination => "saphyr_serde::early_termination",
+            Self::ScanError(_) => "saphyr_serde::syntax",
+            Self::Io(_) => "saphyr_serde::io",
+            Self::InFile { error, .. } => return error.code(),
+        };
+        Some(Box::new(code))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let help = match self {
+            Self::NumberParseError { type_string, .. } => {
+                format!("expected a value that fits in a {type_string}")
+            }
+            Self::BoolParseError { .. } => String::from("booleans are written `true` or `false`"),
+            Self::BinaryParseError { .. } => String::from("`!!binary` scalars hold base64 data"),
+            Self::TypeMismatch { expected, .. } => format!("write {expected} here"),
+            Self::DuplicateKey { key, .. } => format!("remove one of the `{key}` entries"),
+            Self::UnknownField { expected: [], .. } => String::from("this mapping takes no fields"),
+            Self::UnknownField {
+                suggestion: Some(suggestion),
+                ..
+            } => format!("did you mean `{suggestion}`?"),
+            Self::UnknownVariant {
+                suggestion: Some(suggestion),
+                ..
+            } => format!("did you mean `{suggestion}`?"),
+            Self::UnknownField { expected, .. } | Self::UnknownVariant { expected, .. } => {
+                format!("expected one of `{}`", expected.join("`, `"))
+            }
+            Self::DepthLimitExceeded { .. } | Self::LimitExceeded { .. } => {
+                String::from("the input is larger than the deserializer options allow")
+            }
+            Self::ScanError(err) => String::from(err.info()),
+            Self::InFile { error, .. } => return error.help(),
+            _ => return None,
+        };
+        Some(Box::new(help))
+    }
+}
+
+impl DeserializeError {
+    /// Pairs the error with the input it came from, for a miette report whose label points at
+    /// the offending span.
+    ///
+    /// ```
+    /// let input = "città: lots";
+    /// let err = saphyr_serde::from_str::<std::collections::HashMap<String, u32>>(input)
+    ///     .unwrap_err();
+    /// let _report = miette::Report::new(err.with_source_code(input));
+    /// ```
+    pub fn with_source_code(self, source: impl Into<String>) -> SourceDiagnostic {
+        SourceDiagnostic {
+            error: self,
+            source: source.into(),
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::UnexpectedElement { .. } => "unexpected here",
+            Self::NumberParseError { .. } => "not a valid number",
+            Self::BoolParseError { .. } => "not a boolean",
+            Self::BinaryParseError { .. } => "not valid base64",
+            Self::TypeMismatch { .. } => "wrong kind of value",
+            Self::InvalidLength { .. } => "wrong number of elements",
+            Self::DuplicateKey { .. } => "duplicate key",
+            Self::UnknownField { .. } => "unknown field",
+            Self::UnknownVariant { .. } => "unknown variant",
+            Self::UnknownTag { .. } => "unknown tag",
+            Self::ValidationFailed { .. } => "rejected by validator",
+            Self::DepthLimitExceeded { .. } => "nested too deeply",
+            Self::LimitExceeded { .. } => "limit exceeded here",
+            Self::InFile { error, .. } => error.label(),
+            _ => "here",
+        }
+    }
+}
+
+/// A [`DeserializeError`] with the input it came from, see
+/// [`DeserializeError::with_source_code`].
+#[derive(Debug)]
+pub struct SourceDiagnostic {
+    error: DeserializeError,
+    source: String,
+}
+
+impl SourceDiagnostic {
+    pub fn error(&self) -> &DeserializeError {
+        &self.error
+    }
+
+    pub fn into_error(self) -> DeserializeError {
+        self.error
+    }
+}
+
+impl Display for SourceDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for SourceDiagnostic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+impl Diagnostic for SourceDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.code()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.help()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.source)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        // Markers count characters, so go through the line and column to get bytes.
+        let span = self.error.span()?;
+        let start = byte_offset(&self.source, span.start)?;
+        let end = byte_offset(&self.source, span.end).unwrap_or(start);
+        let end = if end > start {
+            end
+        } else {
+            // Cover at least the character at the start.
+            self.source[start..]
+                .chars()
+                .next()
+                .map_or(start, |c| start + c.len_utf8())
+        };
+        Some(Box::new(std::iter::once(LabeledSpan::at(
+            start..end,
+            self.error.label(),
+        ))))
+    }
+}
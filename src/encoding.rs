@@ -0,0 +1,123 @@
+// Detecting and transcoding the encodings a YAML stream may use, as described in section 5.2 of
+// the YAML 1.2 spec.
+
+use std::{fmt, io};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Utf32Le => "UTF-32LE",
+            Encoding::Utf32Be => "UTF-32BE",
+        })
+    }
+}
+
+/// Picks the encoding from a byte order mark, or failing that from where the zero bytes of the
+/// first character fall, which works because a stream must start with an ASCII character.
+pub(crate) fn detect(bytes: &[u8]) -> Encoding {
+    match bytes {
+        [0, 0, 0xfe, 0xff, ..] | [0, 0, 0, _, ..] => Encoding::Utf32Be,
+        [0xff, 0xfe, 0, 0, ..] | [_, 0, 0, 0, ..] => Encoding::Utf32Le,
+        [0xfe, 0xff, ..] | [0, _, ..] => Encoding::Utf16Be,
+        [0xff, 0xfe, ..] | [_, 0, ..] => Encoding::Utf16Le,
+        _ => Encoding::Utf8,
+    }
+}
+
+/// Decodes `bytes` to a string, dropping any byte order mark from a UTF-16 or UTF-32 stream.
+/// With `lossy` set, invalid sequences become U+FFFD instead of failing. Also returns how many
+/// sequences were replaced.
+pub(crate) fn decode(bytes: Vec<u8>, lossy: bool) -> io::Result<(String, usize)> {
+    let encoding = detect(&bytes);
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("input is not valid {encoding}"),
+        )
+    };
+    let mut replacements = 0;
+    let mut replace = || {
+        replacements += 1;
+        Ok(char::REPLACEMENT_CHARACTER)
+    };
+    let decoded = match encoding {
+        Encoding::Utf8 => {
+            return match String::from_utf8(bytes) {
+                Ok(decoded) => Ok((decoded, 0)),
+                Err(err) if lossy => {
+                    let bytes = err.as_bytes();
+                    let replacements = bytes
+                        .utf8_chunks()
+                        .filter(|chunk| !chunk.invalid().is_empty())
+                        .count();
+                    Ok((String::from_utf8_lossy(bytes).into_owned(), replacements))
+                }
+                Err(err) => Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+            };
+        }
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            if !lossy && !bytes.len().is_multiple_of(2) {
+                return Err(invalid());
+            }
+            let units = bytes.chunks_exact(2).map(|unit| {
+                let unit = [unit[0], unit[1]];
+                match encoding {
+                    Encoding::Utf16Le => u16::from_le_bytes(unit),
+                    _ => u16::from_be_bytes(unit),
+                }
+            });
+            let mut decoded: String = char::decode_utf16(units)
+                .map(|c| match c {
+                    Ok(c) => Ok(c),
+                    Err(_err) if lossy => replace(),
+                    Err(_err) => Err(invalid()),
+                })
+                .collect::<io::Result<_>>()?;
+            if !bytes.len().is_multiple_of(2) {
+                decoded.push(replace()?);
+            }
+            decoded
+        }
+        Encoding::Utf32Le | Encoding::Utf32Be => {
+            if !lossy && !bytes.len().is_multiple_of(4) {
+                return Err(invalid());
+            }
+            let mut decoded = bytes
+                .chunks_exact(4)
+                .map(|unit| {
+                    let unit = [unit[0], unit[1], unit[2], unit[3]];
+                    let unit = match encoding {
+                        Encoding::Utf32Le => u32::from_le_bytes(unit),
+                        _ => u32::from_be_bytes(unit),
+                    };
+                    match char::from_u32(unit) {
+                        Some(c) => Ok(c),
+                        None if lossy => replace(),
+                        None => Err(invalid()),
+                    }
+                })
+                .collect::<io::Result<String>>()?;
+            if !bytes.len().is_multiple_of(4) {
+                decoded.push(replace()?);
+            }
+            decoded
+        }
+    };
+    let decoded = match decoded.strip_prefix('\u{feff}') {
+        Some(rest) => rest.to_string(),
+        None => decoded,
+    };
+    Ok((decoded, replacements))
+}
@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use serde::{Serialize, ser::SerializeStruct};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -14,6 +15,8 @@ pub enum DeserializeError {
     UnexpectedElement {
         event_name: String,
         span: saphyr_parser::Span,
+        /// The events consumed just before this one, oldest first.
+        recent_events: Vec<String>,
     },
 
     #[error("Error during deserialization: {0}")]
@@ -33,22 +36,328 @@ pub enum DeserializeError {
         span: saphyr_parser::Span,
     },
 
+    #[error("Unable to decode !!binary data at line {}, column {}", .span.start.line(), .span.start.col())]
+    BinaryParseError { span: saphyr_parser::Span },
+
+    #[error("Expected {expected}{}, found {found} at line {}, column {}", for_path(.path), .span.start.line(), .span.start.col())]
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+        /// Where the value sits, e.g. `servers[0].ports`, or empty for the top level.
+        path: String,
+        span: saphyr_parser::Span,
+    },
+
+    #[error("Found {found} elements, expected {expected}{}", at_location(.span))]
+    InvalidLength {
+        found: usize,
+        expected: String,
+        span: Option<saphyr_parser::Span>,
+    },
+
+    #[error("Duplicate key {key} at line {}, column {}", .span.start.line(), .span.start.col())]
+    DuplicateKey {
+        key: String,
+        span: saphyr_parser::Span,
+    },
+
+    #[error("Nesting deeper than {limit} levels at line {}, column {}", .span.start.line(), .span.start.col())]
+    DepthLimitExceeded {
+        limit: usize,
+        span: saphyr_parser::Span,
+    },
+
+    #[error("Exceeded the {limit} limit of {max} at line {}, column {}", .span.start.line(), .span.start.col())]
+    LimitExceeded {
+        limit: Limit,
+        max: usize,
+        span: saphyr_parser::Span,
+    },
+
+    #[error("Unknown field `{field}`{}{}", did_you_mean(.suggestion), at_location(.span))]
+    UnknownField {
+        field: Box<str>,
+        expected: &'static [&'static str],
+        suggestion: Option<&'static str>,
+        /// Where the mapping holding the field sits, or empty for the top level.
+        path: Box<str>,
+        span: Option<saphyr_parser::Span>,
+    },
+
+    #[error("Unknown variant `{variant}`{}{}", did_you_mean(.suggestion), at_location(.span))]
+    UnknownVariant {
+        variant: String,
+        expected: &'static [&'static str],
+        suggestion: Option<&'static str>,
+        span: Option<saphyr_parser::Span>,
+    },
+
+    #[error("Unknown tag `{tag}` at line {}, column {}", .span.start.line(), .span.start.col())]
+    UnknownTag {
+        tag: String,
+        span: saphyr_parser::Span,
+    },
+
+    #[error("Invalid value at {path} at line {}, column {}: {message}", .span.start.line(), .span.start.col())]
+    ValidationFailed {
+        path: String,
+        message: String,
+        span: saphyr_parser::Span,
+    },
+
     #[error("Unexpected early termination")]
     EarlyTermination,
 
     #[error("Scan error")]
     ScanError(#[from] saphyr_parser::ScanError),
+
+    #[error("IO error: {0}")]
+    Io(#[source] IoError),
+
+    #[error("{}: {error}", .path.display())]
+    InFile {
+        path: std::path::PathBuf,
+        #[source]
+        error: Box<DeserializeError>,
+    },
+}
+
+fn did_you_mean(suggestion: &Option<&'static str>) -> String {
+    suggestion
+        .map(|suggestion| format!(", did you mean `{suggestion}`?"))
+        .unwrap_or_default()
+}
+
+fn for_path(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!(" for `{path}`")
+    }
+}
+
+fn at_location(span: &Option<saphyr_parser::Span>) -> String {
+    span.map(|span| {
+        format!(
+            " at line {}, column {}",
+            span.start.line(),
+            span.start.col()
+        )
+    })
+    .unwrap_or_default()
+}
+
+/// Optimal string alignment distance: edits, including swapping adjacent characters, needed to
+/// turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Appends `segment` to `path`, where an empty path is the top level.
+fn join_path(path: &str, segment: &str) -> String {
+    if path.is_empty() || segment.is_empty() || segment.starts_with('[') {
+        format!("{path}{segment}")
+    } else {
+        format!("{path}.{segment}")
+    }
+}
+
+fn closest_name(field: &str, expected: &'static [&'static str]) -> Option<&'static str> {
+    let threshold = (field.chars().count() / 3).max(1);
+    expected
+        .iter()
+        .map(|candidate| (edit_distance(field, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Wraps the [`std::io::Error`] behind [`DeserializeError::Io`]. IO errors can't be compared
+/// directly, so two of these are equal when their kind and message match.
+#[derive(Debug)]
+pub struct IoError(pub std::io::Error);
+
+impl Display for IoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for IoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.kind() == other.0.kind() && self.0.to_string() == other.0.to_string()
+    }
+}
+
+impl From<std::io::Error> for DeserializeError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(IoError(err))
+    }
+}
+
+/// Broad categories of [`DeserializeError`], for deciding how to handle a failure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorKind {
+    /// The input is not well-formed YAML.
+    Syntax,
+    /// The YAML is well-formed but doesn't have the shape or scalar types the target expects.
+    Type,
+    /// The YAML has the right shape but its content is rejected, e.g. a missing field.
+    Data,
+    /// The input ended before a complete value was read.
+    Eof,
+    /// The input exceeds a limit set in the deserializer options.
+    Limit,
+    /// Reading the input failed.
+    Io,
+}
+
+/// A resource limit configured through [`DeserializerOptions`](crate::options::DeserializerOptions).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    Nodes,
+    ScalarLength,
+    InputSize,
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Limit::Nodes => "node count",
+            Limit::ScalarLength => "scalar length",
+            Limit::InputSize => "input size",
+        })
+    }
 }
 
 impl DeserializeError {
+    /// Where in the input the error occurred, if known. Scan errors only carry a single
+    /// position, so their span starts and ends at the same marker.
+    pub fn span(&self) -> Option<saphyr_parser::Span> {
+        match self {
+            Self::UnexpectedElement { span, .. }
+            | Self::NumberParseError { span, .. }
+            | Self::BoolParseError { span, .. }
+            | Self::BinaryParseError { span }
+            | Self::TypeMismatch { span, .. }
+            | Self::UnknownTag { span, .. }
+            | Self::DuplicateKey { span, .. }
+            | Self::DepthLimitExceeded { span, .. }
+            | Self::LimitExceeded { span, .. }
+            | Self::ValidationFailed { span, .. } => Some(*span),
+            Self::InvalidLength { span, .. }
+            | Self::UnknownField { span, .. }
+            | Self::UnknownVariant { span, .. } => *span,
+            Self::ScanError(err) => Some(saphyr_parser::Span {
+                start: *err.marker(),
+                end: *err.marker(),
+            }),
+            Self::InFile { error, .. } => error.span(),
+            Self::TrailingCharacters
+            | Self::TypeError
+            | Self::SerdeError(_)
+            | Self::EarlyTermination
+            | Self::Io(_) => None,
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::TrailingCharacters | Self::ScanError(_) => ErrorKind::Syntax,
+            Self::TypeError
+            | Self::UnexpectedElement { .. }
+            | Self::NumberParseError { .. }
+            | Self::BoolParseError { .. }
+            | Self::BinaryParseError { .. }
+            | Self::TypeMismatch { .. }
+            | Self::InvalidLength { .. } => ErrorKind::Type,
+            Self::SerdeError(_)
+            | Self::DuplicateKey { .. }
+            | Self::UnknownField { .. }
+            | Self::UnknownVariant { .. }
+            | Self::UnknownTag { .. }
+            | Self::ValidationFailed { .. } => ErrorKind::Data,
+            Self::DepthLimitExceeded { .. } | Self::LimitExceeded { .. } => ErrorKind::Limit,
+            Self::EarlyTermination => ErrorKind::Eof,
+            Self::Io(_) => ErrorKind::Io,
+            Self::InFile { error, .. } => error.kind(),
+        }
+    }
+
+    /// The line the error starts on, numbered as in the error message.
+    pub fn line(&self) -> Option<usize> {
+        self.span().map(|span| span.start.line())
+    }
+
+    /// The column the error starts at, numbered as in the error message.
+    pub fn column(&self) -> Option<usize> {
+        self.span().map(|span| span.start.col())
+    }
+
+    /// Where in the document the offending value sits, e.g. `servers[0].ports`, for the errors
+    /// that track it. The top level is the empty path.
+    pub fn path(&self) -> Option<String> {
+        match self {
+            Self::TypeMismatch { path, .. } | Self::ValidationFailed { path, .. } => {
+                Some(path.clone())
+            }
+            Self::UnknownField { path, field, .. } => Some(join_path(path, field)),
+            Self::InFile { error, .. } => error.path(),
+            _ => None,
+        }
+    }
+
+    /// Where the error occurred, in the shape of `serde_yaml::Location`.
+    pub fn location(&self) -> Option<crate::compat::Location> {
+        self.span()
+            .map(|span| crate::compat::Location::new(span.start))
+    }
+
+    /// Renders the error followed by the offending line of `source` with the span underlined,
+    /// falling back to the plain message when the error has no location.
+    pub fn display_with_source<'a>(&'a self, source: &'a str) -> WithSource<'a> {
+        WithSource {
+            error: self,
+            source,
+        }
+    }
+
     pub(crate) fn unexpected(
         event: &saphyr_parser::Event,
         span: saphyr_parser::Span,
         location: &str,
+        recent_events: Vec<String>,
     ) -> Self {
         Self::UnexpectedElement {
             event_name: format!("{:?} (in {})", event, location),
             span,
+            recent_events,
         }
     }
 
@@ -72,6 +381,106 @@ impl DeserializeError {
             span,
         }
     }
+
+    pub(crate) fn invalid_binary(span: saphyr_parser::Span) -> DeserializeError {
+        Self::BinaryParseError { span }
+    }
+
+    /// Attach the position of the node being read to the errors serde raises without one,
+    /// such as an unknown field name or enum variant.
+    pub(crate) fn at_node(mut self, node_span: Option<saphyr_parser::Span>) -> DeserializeError {
+        if let Self::UnknownField { span, .. }
+        | Self::UnknownVariant { span, .. }
+        | Self::InvalidLength { span, .. } = &mut self
+            && span.is_none()
+        {
+            *span = node_span;
+        }
+        self
+    }
+
+    pub(crate) fn type_mismatch(
+        expected: &'static str,
+        found: &'static str,
+        span: saphyr_parser::Span,
+    ) -> DeserializeError {
+        Self::TypeMismatch {
+            expected,
+            found,
+            path: String::new(),
+            span,
+        }
+    }
+
+    /// Prefix the path of a [`TypeMismatch`](Self::TypeMismatch) with the key of the mapping
+    /// value it was raised in, as the error travels outwards.
+    pub(crate) fn in_key(self, key: &str) -> DeserializeError {
+        self.prefix_path(key)
+    }
+
+    /// Like [`Self::in_key`], for the index of a sequence element.
+    pub(crate) fn in_element(self, index: usize) -> DeserializeError {
+        self.prefix_path(&format!("[{index}]"))
+    }
+
+    fn prefix_path(mut self, segment: &str) -> DeserializeError {
+        match &mut self {
+            Self::TypeMismatch { path, .. } => *path = join_path(segment, path),
+            Self::UnknownField { path, .. } => *path = join_path(segment, path).into(),
+            _ => {}
+        }
+        self
+    }
+
+    pub(crate) fn invalid_length(
+        found: usize,
+        expected: String,
+        span: Option<saphyr_parser::Span>,
+    ) -> DeserializeError {
+        Self::InvalidLength {
+            found,
+            expected,
+            span,
+        }
+    }
+
+    pub(crate) fn duplicate_key(key: &str, span: saphyr_parser::Span) -> DeserializeError {
+        Self::DuplicateKey {
+            key: String::from(key),
+            span,
+        }
+    }
+
+    pub(crate) fn unknown_tag(tag: String, span: saphyr_parser::Span) -> DeserializeError {
+        Self::UnknownTag { tag, span }
+    }
+
+    pub(crate) fn validation_failed(
+        path: &str,
+        message: String,
+        span: saphyr_parser::Span,
+    ) -> DeserializeError {
+        Self::ValidationFailed {
+            path: String::from(path),
+            message,
+            span,
+        }
+    }
+
+    pub(crate) fn depth_limit_exceeded(
+        limit: usize,
+        span: saphyr_parser::Span,
+    ) -> DeserializeError {
+        Self::DepthLimitExceeded { limit, span }
+    }
+
+    pub(crate) fn limit_exceeded(
+        limit: Limit,
+        max: usize,
+        span: saphyr_parser::Span,
+    ) -> DeserializeError {
+        Self::LimitExceeded { limit, max, span }
+    }
 }
 
 impl serde::de::Error for DeserializeError {
@@ -110,6 +519,85 @@ impl serde::de::Error for DeserializeError {
     {
         Self::SerdeError(format!("{}", msg))
     }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        Self::UnknownField {
+            field: field.into(),
+            expected,
+            suggestion: closest_name(field, expected),
+            path: Box::default(),
+            span: None,
+        }
+    }
+
+    fn invalid_length(len: usize, expected: &dyn serde::de::Expected) -> Self {
+        Self::invalid_length(len, expected.to_string(), None)
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        Self::UnknownVariant {
+            variant: String::from(variant),
+            expected,
+            suggestion: closest_name(variant, expected),
+            span: None,
+        }
+    }
+}
+
+/// Serializes as a diagnostic record with `kind`, `message`, `line`, `column` and `path` fields,
+/// for forwarding errors to clients.
+impl Serialize for DeserializeError {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("DeserializeError", 5)?;
+        state.serialize_field("kind", &self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("line", &self.line())?;
+        state.serialize_field("column", &self.column())?;
+        state.serialize_field("path", &self.path())?;
+        state.end()
+    }
+}
+
+/// An error paired with the input it came from, see [`DeserializeError::display_with_source`].
+pub struct WithSource<'a> {
+    error: &'a DeserializeError,
+    source: &'a str,
+}
+
+impl Display for WithSource<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)?;
+        let Some(span) = self.error.span() else {
+            return Ok(());
+        };
+        let line_number = span.start.line();
+        let Some(line) = self.source.lines().nth(line_number.saturating_sub(1)) else {
+            return Ok(());
+        };
+        let line_width = line.chars().count();
+        let start = span.start.col().min(line_width);
+        let end = if span.end.line() == line_number {
+            span.end.col().clamp(start, line_width)
+        } else {
+            line_width
+        };
+        let gutter = " ".repeat(line_number.to_string().len());
+        writeln!(f)?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{line_number} | {line}")?;
+        write!(
+            f,
+            "{gutter} | {}^{}",
+            " ".repeat(start),
+            "~".repeat(end.saturating_sub(start + 1))
+        )
+    }
 }
 
 pub type Result<T> = std::result::Result<T, DeserializeError>;
+
+#[cfg(feature = "miette")]
+pub use crate::diagnostic::SourceDiagnostic;
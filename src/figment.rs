@@ -0,0 +1,28 @@
+//! A [figment](https://docs.rs/figment) format backed by this crate's deserializer, for use in
+//! place of figment's own `Yaml`.
+//!
+//! ```
+//! use figment::{Figment, providers::Format};
+//! use saphyr_serde::figment::Yaml;
+//!
+//! let figment = Figment::new().merge(Yaml::string("name: widget"));
+//! ```
+
+use ::figment::providers::Format;
+use serde::de::DeserializeOwned;
+
+use crate::error::DeserializeError;
+
+/// YAML files and strings as a figment provider, through [`Format::file`] and
+/// [`Format::string`].
+pub struct Yaml;
+
+impl Format for Yaml {
+    type Error = DeserializeError;
+
+    const NAME: &'static str = "YAML";
+
+    fn from_str<T: DeserializeOwned>(string: &str) -> Result<T, Self::Error> {
+        crate::de::from_str(string)
+    }
+}
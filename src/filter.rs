@@ -0,0 +1,109 @@
+//! Choosing which documents of a stream to deserialize, through
+//! [`Deserializer::filter_deserialize`](crate::de::Deserializer::filter_deserialize).
+
+use std::borrow::Cow;
+
+use saphyr_parser::Event;
+use serde::Deserialize;
+
+use crate::{
+    de::{Deserializer, StreamDeserializer},
+    error::Result,
+};
+
+/// The scalar entries a document's top-level mapping starts with, such as `kind` and
+/// `apiVersion`, for deciding whether to deserialize it. The head ends at the first entry whose
+/// key or value is a sequence, mapping or alias, so only the entries before it are read ahead and
+/// held in memory, never a nested value. A document that isn't a mapping has an empty head.
+#[derive(Debug, Default)]
+pub struct DocumentHead<'de> {
+    entries: Vec<(Cow<'de, str>, Cow<'de, str>)>,
+}
+
+impl<'de> DocumentHead<'de> {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _value)| k == key)
+            .map(|(_key, value)| value.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_ref(), value.as_ref()))
+    }
+
+    /// Reads the head of the document `de` has just entered. The events it looks at are kept
+    /// in `de` to be deserialized.
+    pub(crate) fn scan(de: &mut Deserializer<'de>) -> Result<Self> {
+        let mut head = DocumentHead::default();
+        if !matches!(de.read_ahead()?, Event::MappingStart(..)) {
+            return Ok(head);
+        }
+        while let Event::Scalar(key, ..) = de.read_ahead()? {
+            let Event::Scalar(value, ..) = de.read_ahead()? else {
+                break;
+            };
+            head.entries.push((key, value));
+        }
+        Ok(head)
+    }
+}
+
+/// An iterator over the documents of a stream that match a predicate, created by
+/// [`Deserializer::filter_deserialize`](crate::de::Deserializer::filter_deserialize). It stops
+/// after the first error.
+pub struct FilterDocuments<'de, T, F> {
+    documents: StreamDeserializer<'de, T>,
+    predicate: F,
+    finished: bool,
+}
+
+impl<'de, T, F> FilterDocuments<'de, T, F>
+where
+    T: Deserialize<'de>,
+    F: FnMut(&DocumentHead<'de>) -> bool,
+{
+    pub(crate) fn new(documents: StreamDeserializer<'de, T>, predicate: F) -> Self {
+        FilterDocuments {
+            documents,
+            predicate,
+            finished: false,
+        }
+    }
+
+    fn next_match(&mut self) -> Result<Option<T>> {
+        loop {
+            match self.documents.next_document_where(&mut self.predicate)? {
+                Some(Some(t)) => return Ok(Some(t)),
+                Some(None) => {}
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+impl<'de, T, F> Iterator for FilterDocuments<'de, T, F>
+where
+    T: Deserialize<'de>,
+    F: FnMut(&DocumentHead<'de>) -> bool,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let next = self.next_match().transpose();
+        self.finished = !matches!(next, Some(Ok(_)));
+        next
+    }
+}
+
+impl<'de, T, F> std::iter::FusedIterator for FilterDocuments<'de, T, F>
+where
+    T: Deserialize<'de>,
+    F: FnMut(&DocumentHead<'de>) -> bool,
+{
+}
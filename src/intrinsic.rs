@@ -0,0 +1,61 @@
+// Expanding CloudFormation's short-form intrinsic function tags, `!Ref Bucket`, into the long
+// form, `{Ref: Bucket}`, the way cfn-flip does.
+
+use saphyr_parser::Event;
+use serde::de::{DeserializeSeed, IntoDeserializer, MapAccess, value::SeqDeserializer};
+
+use crate::{de::Deserializer, error::DeserializeError};
+
+/// The key of the long form of the intrinsic function a local tag names.
+pub(crate) fn long_form(tag: &str) -> Option<String> {
+    match tag {
+        "Ref" | "Condition" => Some(tag.to_string()),
+        "And" | "Base64" | "Cidr" | "Equals" | "FindInMap" | "GetAtt" | "GetAZs" | "If"
+        | "ImportValue" | "Join" | "Length" | "Not" | "Or" | "Select" | "Split" | "Sub"
+        | "ToJsonString" | "Transform" => Some(format!("Fn::{tag}")),
+        _ => None,
+    }
+}
+
+/// The single entry mapping a tagged node expands to.
+pub(crate) struct Intrinsic<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    key: Option<String>,
+}
+
+impl<'a, 'de> Intrinsic<'a, 'de> {
+    pub(crate) fn new(de: &'a mut Deserializer<'de>, key: String) -> Self {
+        Self { de, key: Some(key) }
+    }
+}
+
+impl<'de> MapAccess<'de> for Intrinsic<'_, 'de> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match &self.key {
+            Some(key) => seed.deserialize(key.as_str().into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let key = self.key.take();
+        // `!GetAtt Bucket.Arn` is the short form of `Fn::GetAtt: [Bucket, Arn]`.
+        if key.as_deref() == Some("Fn::GetAtt")
+            && let Some((Event::Scalar(attribute, _, _, _), _span)) = self.de.peek_event()?
+            && let Some((resource, name)) = attribute.split_once('.')
+        {
+            let parts = [resource.to_string(), name.to_string()];
+            self.de.next_event()?;
+            return seed.deserialize(SeqDeserializer::new(parts.into_iter()));
+        }
+        seed.deserialize(&mut *self.de)
+    }
+}
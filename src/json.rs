@@ -0,0 +1,50 @@
+//! Converting YAML to JSON without building an intermediate value.
+//!
+//! Plain scalars are written as the null, boolean or number they resolve to, and quoted
+//! scalars as strings.
+
+use std::io::Write;
+
+use crate::{
+    de::Deserializer,
+    error::{DeserializeError, Result},
+    options::DeserializerOptions,
+};
+
+/// Streams the YAML document in `input` to `writer` as compact JSON.
+///
+/// ```
+/// let mut json = Vec::new();
+/// saphyr_serde::json::transcode_to_json("name: widget\ntags: [a, '1', 2]", &mut json).unwrap();
+/// assert_eq!(json, br#"{"name":"widget","tags":["a","1",2]}"#);
+/// ```
+pub fn transcode_to_json<W>(input: &str, writer: W) -> Result<()>
+where
+    W: Write,
+{
+    transcode_to_json_with_options(input, writer, DeserializerOptions::default())
+}
+
+pub fn transcode_to_json_with_options<W>(
+    input: &str,
+    writer: W,
+    options: DeserializerOptions,
+) -> Result<()>
+where
+    W: Write,
+{
+    let mut serializer = serde_json::Serializer::new(writer);
+    let mut deserializer = Deserializer::from_str_with_options(input, options);
+    deserializer.start_stream()?;
+    deserializer.start_document()?;
+    serde_transcode::transcode(&mut deserializer, &mut serializer).map_err(json_error)?;
+    deserializer.end()
+}
+
+fn json_error(err: serde_json::Error) -> DeserializeError {
+    if err.is_io() {
+        std::io::Error::from(err).into()
+    } else {
+        DeserializeError::SerdeError(err.to_string())
+    }
+}
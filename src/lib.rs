@@ -1,7 +1,49 @@
+pub mod compat;
 pub mod de;
+pub mod debug;
+#[cfg(feature = "miette")]
+mod diagnostic;
+mod encoding;
 pub mod error;
+#[cfg(feature = "figment")]
+pub mod figment;
+pub mod filter;
+mod intrinsic;
+#[cfg(feature = "json")]
+pub mod json;
 mod mapping;
+pub mod options;
+pub mod position;
+mod scalar;
 mod seq;
+pub mod source_map;
+pub mod tagged;
+pub mod template;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 #[cfg(test)]
 mod tests;
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod timestamp;
+pub mod validate;
 mod variant;
+pub mod warning;
+pub mod with;
+
+pub use de::{
+    Deserializer, StreamDeserializer, check, check_with_options, from_file, from_file_with_options,
+    from_reader, from_reader_with_options, from_slice, from_slice_owned,
+    from_slice_owned_with_options, from_slice_with_options, from_str, from_str_all,
+    from_str_all_with_options, from_str_seed, from_str_seed_with_options, from_str_with_options,
+    from_str_with_source_map, from_str_with_source_map_with_options, from_string,
+    from_string_with_options,
+};
+#[cfg(feature = "tokio")]
+pub use de::{from_async_reader, from_async_reader_with_options};
+#[cfg(feature = "flate2")]
+pub use de::{from_gzip_reader, from_gzip_reader_with_options};
+pub use error::{DeserializeError, ErrorKind, Result};
+pub use options::DeserializerOptions;
+pub use source_map::SourceMap;
+pub use tagged::Tagged;
+pub use template::TemplateOr;
@@ -1,19 +1,83 @@
-use saphyr_parser::Event;
+use std::{borrow::Cow, collections::HashSet};
+
+use saphyr_parser::{Event, Span};
 use serde::de::{DeserializeSeed, MapAccess};
 
-use crate::{de::Deserializer, error::DeserializeError};
+use crate::{
+    de::{Deserializer, nested},
+    error::DeserializeError,
+    position,
+};
+
+/// The key whose value is being read, for paths in errors and validators.
+enum Key {
+    Text(String),
+    /// A key that would have to be copied out of the parser, found again in the input from its
+    /// span if an error needs it.
+    At(Span),
+}
 
 pub struct YamlMapping<'a, 'de: 'a> {
     de: &'a mut Deserializer<'de>,
     empty: bool,
+    seen_keys: HashSet<String>,
+    key: Option<Key>,
 }
 impl<'a, 'de> YamlMapping<'a, 'de> {
     pub(crate) fn new(de: &'a mut Deserializer<'de>) -> Self {
-        Self { de, empty: false }
+        Self {
+            de,
+            empty: false,
+            seen_keys: HashSet::new(),
+            key: None,
+        }
     }
 
     pub(crate) fn empty(de: &'a mut Deserializer<'de>) -> Self {
-        Self { de, empty: true }
+        Self {
+            de,
+            empty: true,
+            seen_keys: HashSet::new(),
+            key: None,
+        }
+    }
+
+    fn check_duplicate_key(&mut self) -> Result<(), DeserializeError> {
+        if !self.de.options.deny_duplicate_keys {
+            return Ok(());
+        }
+        if let Some((key, span)) = self.de.peek_scalar_string()
+            && !self.seen_keys.insert(key.to_string())
+        {
+            return Err(DeserializeError::duplicate_key(key, span));
+        }
+        Ok(())
+    }
+
+    /// Notes the key about to be read. Its text is only kept when the path is being tracked, so
+    /// reading a mapping doesn't copy every key.
+    fn capture_key(&mut self) -> Option<Span> {
+        let tracks_path = self.de.tracks_path();
+        let (key, span) = match self.de.peek_event().ok()?? {
+            (Event::Scalar(text, ..), span) if tracks_path => {
+                (Some(Key::Text(text.to_string())), *span)
+            }
+            (Event::Scalar(..), span) => (Some(Key::At(*span)), *span),
+            (_event, span) => (None, *span),
+        };
+        self.key = key;
+        Some(span)
+    }
+
+    /// The text of a key, as written in the input when it wasn't kept.
+    fn key_text(&self, key: Key) -> Cow<'de, str> {
+        match key {
+            Key::Text(text) => Cow::Owned(text),
+            Key::At(span) => Cow::Borrowed(
+                position::slice(self.de.input, span)
+                    .map_or("?", |text| text.trim_matches(['"', '\''])),
+            ),
+        }
     }
 }
 
@@ -27,11 +91,17 @@ impl<'de, 'a> MapAccess<'de> for YamlMapping<'a, 'de> {
         if self.empty {
             Ok(None)
         } else {
-            match self.de.peek_event() {
+            match self.de.peek_event()? {
                 Some((Event::DocumentEnd, _span)) => Ok(None),
                 Some((Event::StreamEnd, _span)) => Ok(None),
                 Some((Event::MappingEnd, _span)) => Ok(None),
-                _ => seed.deserialize(&mut *self.de).map(Some),
+                _ => {
+                    self.check_duplicate_key()?;
+                    let key_span = self.capture_key();
+                    seed.deserialize(&mut *self.de)
+                        .map(Some)
+                        .map_err(|err| err.at_node(key_span))
+                }
             }
         }
     }
@@ -40,6 +110,25 @@ impl<'de, 'a> MapAccess<'de> for YamlMapping<'a, 'de> {
     where
         V: DeserializeSeed<'de>,
     {
-        seed.deserialize(&mut *self.de)
+        let key = self.key.take();
+        let value = if self.de.tracks_path() {
+            let text = match &key {
+                Some(Key::Text(text)) => text.as_ref(),
+                _ => "?",
+            };
+            self.de.path.push_key(text);
+            let value = self
+                .de
+                .enter_value()
+                .and_then(|()| nested(|| seed.deserialize(&mut *self.de)));
+            self.de.leave_value();
+            value
+        } else {
+            nested(|| seed.deserialize(&mut *self.de))
+        };
+        match key {
+            Some(key) => value.map_err(|err| err.in_key(&self.key_text(key))),
+            None => value,
+        }
     }
 }
@@ -0,0 +1,191 @@
+use crate::warning::Warnings;
+
+/// The nesting limit applied by [`DeserializerOptions::strict`].
+pub const STRICT_MAX_DEPTH: usize = 128;
+/// The node limit applied by [`DeserializerOptions::strict`].
+pub const STRICT_MAX_NODES: usize = 1_000_000;
+/// The scalar length limit applied by [`DeserializerOptions::strict`].
+pub const STRICT_MAX_SCALAR_LENGTH: usize = 1024 * 1024;
+/// The input size limit applied by [`DeserializerOptions::strict`].
+pub const STRICT_MAX_INPUT_SIZE: usize = 64 * 1024 * 1024;
+
+/// The YAML schema used to resolve plain scalars into booleans, numbers and nulls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Schema {
+    /// YAML 1.1 resolution, where `y`, `yes`, `on` and friends are booleans, `0777` is octal and
+    /// `1:30` is a base 60 number.
+    Yaml11,
+    /// YAML 1.2 core schema resolution, where only `true` and `false` are booleans.
+    #[default]
+    Yaml12,
+}
+
+/// What to do with a node whose tag the deserializer doesn't recognise, such as `!Ref` or
+/// `!!python/object`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownTags {
+    /// Read the node as though it had no tag.
+    #[default]
+    Ignore,
+    /// Fail with [`UnknownTag`](crate::error::DeserializeError::UnknownTag).
+    Error,
+}
+
+/// Settings controlling how a [`Deserializer`](crate::de::Deserializer) interprets its input.
+///
+/// ```
+/// use saphyr_serde::options::{DeserializerOptions, Schema};
+///
+/// let options = DeserializerOptions::new()
+///     .schema(Schema::Yaml12)
+///     .deny_duplicate_keys(true)
+///     .max_depth(64);
+/// let _de = saphyr_serde::de::Deserializer::from_str_with_options("a: 1", options);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DeserializerOptions {
+    pub(crate) schema: Schema,
+    pub(crate) deny_duplicate_keys: bool,
+    pub(crate) case_insensitive_enums: bool,
+    pub(crate) tagged_enums: bool,
+    pub(crate) unknown_tags: UnknownTags,
+    pub(crate) whole_floats_as_integers: bool,
+    pub(crate) lossy_utf8: bool,
+    pub(crate) warnings: Option<Warnings>,
+    pub(crate) template_expressions: bool,
+    pub(crate) cloudformation_tags: bool,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_nodes: Option<usize>,
+    pub(crate) max_scalar_length: Option<usize>,
+    pub(crate) max_input_size: Option<usize>,
+}
+
+impl DeserializerOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A preset for untrusted input: YAML 1.2 scalar resolution, duplicate keys and unknown tags
+    /// rejected, nesting bounded to [`STRICT_MAX_DEPTH`] levels and at most [`STRICT_MAX_NODES`]
+    /// nodes of which no scalar is longer than [`STRICT_MAX_SCALAR_LENGTH`] bytes, in input of
+    /// at most [`STRICT_MAX_INPUT_SIZE`] bytes.
+    ///
+    /// Trailing content and extra documents are always rejected by
+    /// [`from_str`](crate::de::from_str), so they need no separate setting here.
+    pub fn strict() -> Self {
+        Self::new()
+            .schema(Schema::Yaml12)
+            .deny_duplicate_keys(true)
+            .unknown_tags(UnknownTags::Error)
+            .max_depth(STRICT_MAX_DEPTH)
+            .max_nodes(STRICT_MAX_NODES)
+            .max_scalar_length(STRICT_MAX_SCALAR_LENGTH)
+            .max_input_size(STRICT_MAX_INPUT_SIZE)
+    }
+
+    /// Select the schema used when resolving plain scalars.
+    pub fn schema(mut self, schema: Schema) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Opt into the YAML 1.1 scalar rules, for files written before YAML 1.2. Shorthand for
+    /// [`schema(Schema::Yaml11)`](Self::schema).
+    pub fn legacy_1_1(self, legacy: bool) -> Self {
+        self.schema(if legacy {
+            Schema::Yaml11
+        } else {
+            Schema::Yaml12
+        })
+    }
+
+    /// Report an error when a mapping contains the same scalar key more than once, rather than
+    /// letting the target type decide.
+    pub fn deny_duplicate_keys(mut self, deny: bool) -> Self {
+        self.deny_duplicate_keys = deny;
+        self
+    }
+
+    /// Match enum variants ignoring case and `-`/`_` separators, so `value-a` selects `ValueA`.
+    pub fn case_insensitive_enums(mut self, enabled: bool) -> Self {
+        self.case_insensitive_enums = enabled;
+        self
+    }
+
+    /// Also accept enums written with the variant as a local tag, `!Request { id: foo }` for
+    /// `{ Request: { id: foo } }`. An untagged `!Unit` is a unit variant.
+    pub fn tagged_enums(mut self, enabled: bool) -> Self {
+        self.tagged_enums = enabled;
+        self
+    }
+
+    /// Choose how tags other than the core `!!` tags are handled. Local tags naming an enum
+    /// variant are recognised when [`Self::tagged_enums`] is enabled.
+    pub fn unknown_tags(mut self, policy: UnknownTags) -> Self {
+        self.unknown_tags = policy;
+        self
+    }
+
+    /// Accept floats with no fractional part, such as `3.0` or `1e3`, for integer fields.
+    /// Anything else, including `3.5`, is still an error.
+    pub fn whole_floats_as_integers(mut self, enabled: bool) -> Self {
+        self.whole_floats_as_integers = enabled;
+        self
+    }
+
+    /// Replace invalid UTF-8, UTF-16 or UTF-32 sequences with U+FFFD instead of failing,
+    /// raising a [`Warning`](crate::warning::Warning) when any were replaced. This applies to
+    /// the functions that copy their input, such as [`from_reader`](crate::from_reader) and
+    /// [`from_slice_owned`](crate::from_slice_owned). [`from_slice`](crate::from_slice) can't
+    /// borrow from a repaired copy, so it refuses to run with this set.
+    pub fn lossy_utf8(mut self, enabled: bool) -> Self {
+        self.lossy_utf8 = enabled;
+        self
+    }
+
+    /// Collect the warnings raised while deserializing into `warnings`.
+    pub fn warnings(mut self, warnings: Warnings) -> Self {
+        self.warnings = Some(warnings);
+        self
+    }
+
+    /// Read scalars that are template expressions, `{{ ... }}` or `${{ ... }}`, into
+    /// [`TemplateOr`](crate::TemplateOr) fields as the template, whatever type the field holds
+    /// otherwise.
+    pub fn template_expressions(mut self, enabled: bool) -> Self {
+        self.template_expressions = enabled;
+        self
+    }
+
+    /// Expand CloudFormation's short-form intrinsic function tags into their long form, as
+    /// cfn-flip does: `!Ref Bucket` reads as `{Ref: Bucket}`, `!Sub '...'` as
+    /// `{Fn::Sub: '...'}` and `!GetAtt Bucket.Arn` as `{Fn::GetAtt: [Bucket, Arn]}`.
+    pub fn cloudformation_tags(mut self, enabled: bool) -> Self {
+        self.cloudformation_tags = enabled;
+        self
+    }
+
+    /// Limit how deeply sequences and mappings may be nested.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Limit the total number of scalars, aliases, sequences and mappings in the input.
+    pub fn max_nodes(mut self, nodes: usize) -> Self {
+        self.max_nodes = Some(nodes);
+        self
+    }
+
+    /// Limit the length in bytes of any single scalar.
+    pub fn max_scalar_length(mut self, length: usize) -> Self {
+        self.max_scalar_length = Some(length);
+        self
+    }
+
+    /// Refuse input longer than `size` bytes before parsing any of it.
+    pub fn max_input_size(mut self, size: usize) -> Self {
+        self.max_input_size = Some(size);
+        self
+    }
+}
@@ -0,0 +1,56 @@
+//! Converting between the markers in spans and byte offsets into the input, for reporting
+//! errors against the source text.
+//!
+//! Markers count lines from 1 and columns from 0, both in characters, so they are converted
+//! through the line and column rather than the marker's index.
+//!
+//! ```
+//! use saphyr_serde::position;
+//!
+//! let input = "port: 80\nretries: three\n";
+//! let err = saphyr_serde::from_str::<std::collections::HashMap<String, u16>>(input).unwrap_err();
+//! let span = err.span().unwrap();
+//! assert_eq!(position::slice(input, span), Some("three"));
+//! assert_eq!(position::byte_offset(input, span.start), Some(18));
+//! ```
+
+use saphyr_parser::{Marker, Span};
+
+/// The byte offset of `marker` in `source`, or `None` if it lies past the end of its line or of
+/// the input.
+pub fn byte_offset(source: &str, marker: Marker) -> Option<usize> {
+    let line_start = if marker.line() <= 1 {
+        0
+    } else {
+        source
+            .match_indices('\n')
+            .nth(marker.line() - 2)
+            .map(|(index, _)| index + 1)?
+    };
+    let line = source[line_start..].split('\n').next()?;
+    if marker.col() == line.chars().count() {
+        return Some(line_start + line.len());
+    }
+    line.char_indices()
+        .nth(marker.col())
+        .map(|(index, _)| line_start + index)
+}
+
+/// The marker for byte `offset` of `source`, or `None` if the offset is past the end or not on
+/// a character boundary.
+pub fn marker_at(source: &str, offset: usize) -> Option<Marker> {
+    let before = source.get(..offset)?;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    Some(Marker::new(
+        before.chars().count(),
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count(),
+    ))
+}
+
+/// The text `span` covers in `source`.
+pub fn slice(source: &str, span: Span) -> Option<&str> {
+    let start = byte_offset(source, span.start)?;
+    let end = byte_offset(source, span.end)?;
+    source.get(start..end)
+}
@@ -0,0 +1,275 @@
+// Resolution rules for plain scalars that aren't covered by Rust's `FromStr` impls.
+
+use crate::options::Schema;
+
+pub(crate) fn resolve_bool(s: &str, schema: Schema) -> Option<bool> {
+    match (schema, s) {
+        (_, "true" | "True" | "TRUE") => Some(true),
+        (_, "false" | "False" | "FALSE") => Some(false),
+        (Schema::Yaml11, "y" | "Y" | "yes" | "Yes" | "YES" | "on" | "On" | "ON") => Some(true),
+        (Schema::Yaml11, "n" | "N" | "no" | "No" | "NO" | "off" | "Off" | "OFF") => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether `tag` is the core schema tag `!!suffix`.
+pub(crate) fn is_core_tag(tag: &saphyr_parser::Tag, suffix: &str) -> bool {
+    tag.handle == "tag:yaml.org,2002:" && tag.suffix == suffix
+}
+
+/// Whether `tag` is one of the types in the YAML type repository, or the non-specific `!`.
+pub(crate) fn is_known_tag(tag: &saphyr_parser::Tag) -> bool {
+    const CORE: [&str; 14] = [
+        "str",
+        "int",
+        "float",
+        "bool",
+        "null",
+        "binary",
+        "timestamp",
+        "set",
+        "omap",
+        "pairs",
+        "map",
+        "seq",
+        "merge",
+        "value",
+    ];
+    (tag.handle == "!" && tag.suffix.is_empty())
+        || CORE.iter().any(|suffix| is_core_tag(tag, suffix))
+}
+
+/// Writes a tag the way it usually appears in the source, `!!int` or `!Ref`.
+pub(crate) fn display_tag(tag: &saphyr_parser::Tag) -> String {
+    if tag.handle == "tag:yaml.org,2002:" {
+        format!("!!{}", tag.suffix)
+    } else {
+        format!("{}{}", tag.handle, tag.suffix)
+    }
+}
+
+/// The name in a local tag such as `!Request`.
+pub(crate) fn local_tag(tag: &saphyr_parser::Tag) -> Option<&str> {
+    (tag.handle == "!" && !tag.suffix.is_empty()).then_some(tag.suffix.as_str())
+}
+
+/// Whether a scalar resolves to null. An empty plain scalar, as in `key:`, is null, while a
+/// quoted `''` is the empty string.
+pub(crate) fn is_null_scalar(s: &str, style: saphyr_parser::ScalarStyle) -> bool {
+    is_null(s) || (s.is_empty() && style == saphyr_parser::ScalarStyle::Plain)
+}
+
+pub(crate) fn is_null(s: &str) -> bool {
+    matches!(s, "null" | "Null" | "NULL" | "~")
+}
+
+fn split_sign(s: &str) -> (bool, &str) {
+    match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    }
+}
+
+fn digits_without_underscores(s: &str, radix: u32) -> Option<i128> {
+    let mut value: i128 = 0;
+    let mut seen_digit = false;
+    for c in s.chars() {
+        if c == '_' {
+            continue;
+        }
+        value = value
+            .checked_mul(radix as i128)?
+            .checked_add(c.to_digit(radix)? as i128)?;
+        seen_digit = true;
+    }
+    seen_digit.then_some(value)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum IntParseError {
+    Empty,
+    InvalidDigit { digit: char, index: usize },
+    TooLarge,
+    TooSmall,
+}
+
+impl std::fmt::Display for IntParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("cannot parse integer from empty string"),
+            Self::InvalidDigit { digit, index } => {
+                write!(f, "invalid digit `{digit}` at position {index}")
+            }
+            Self::TooLarge => f.write_str("number too large to fit in target type"),
+            Self::TooSmall => f.write_str("number too small to fit in target type"),
+        }
+    }
+}
+
+/// Parses an integer with an optional sign, `0x`/`0o`/`0b` radix prefix and `_` separators,
+/// plus the YAML 1.1 forms when that schema is selected.
+pub(crate) fn parse_int<T>(s: &str, schema: Schema) -> Result<T, IntParseError>
+where
+    T: TryFrom<i128>,
+{
+    let legacy = match schema {
+        Schema::Yaml11 => parse_yaml11_int(s),
+        Schema::Yaml12 => None,
+    };
+    let value = match legacy {
+        Some(value) => value,
+        None => parse_prefixed_int(s)?,
+    };
+    T::try_from(value).map_err(|_e| {
+        if value < 0 {
+            IntParseError::TooSmall
+        } else {
+            IntParseError::TooLarge
+        }
+    })
+}
+
+fn parse_prefixed_int(s: &str) -> Result<i128, IntParseError> {
+    let (negative, unsigned) = split_sign(s);
+    let (radix, digits) = match unsigned.get(..2) {
+        Some("0x") => (16, &unsigned[2..]),
+        Some("0o") => (8, &unsigned[2..]),
+        Some("0b") => (2, &unsigned[2..]),
+        _ => (10, unsigned),
+    };
+    let offset = s.len() - digits.len();
+    let mut value: i128 = 0;
+    let mut seen_digit = false;
+    for (index, c) in digits.char_indices() {
+        if c == '_' {
+            continue;
+        }
+        let digit = c.to_digit(radix).ok_or(IntParseError::InvalidDigit {
+            digit: c,
+            index: offset + index,
+        })?;
+        value = value
+            .checked_mul(radix as i128)
+            .and_then(|value| value.checked_add(digit as i128))
+            .ok_or(if negative {
+                IntParseError::TooSmall
+            } else {
+                IntParseError::TooLarge
+            })?;
+        seen_digit = true;
+    }
+    if !seen_digit {
+        return Err(IntParseError::Empty);
+    }
+    Ok(if negative { -value } else { value })
+}
+
+/// YAML 1.1 integers that Rust doesn't parse: `0777` octal and `190:20:30` base 60.
+fn parse_yaml11_int(s: &str) -> Option<i128> {
+    let (negative, digits) = split_sign(s);
+    let value = if digits.len() > 1 && digits.starts_with('0') {
+        digits_without_underscores(&digits[1..], 8)?
+    } else if digits.contains(':') {
+        parse_base60(digits)?
+    } else {
+        return None;
+    };
+    Some(if negative { -value } else { value })
+}
+
+/// Resolves a plain scalar the YAML core schema reads as a float: `1.5`, `-2e3`, `.inf`,
+/// `.nan`, plus base 60 floats under YAML 1.1. Unlike Rust's float parsing it rejects `inf`
+/// and `nan` spelled without the dot, which YAML reads as strings.
+pub(crate) fn resolve_float(s: &str, schema: Schema) -> Option<f64> {
+    if schema == Schema::Yaml11
+        && let Some(value) = parse_yaml11_float(s)
+    {
+        return Some(value);
+    }
+    let (negative, unsigned) = split_sign(s);
+    let value = match unsigned {
+        ".inf" | ".Inf" | ".INF" => f64::INFINITY,
+        ".nan" | ".NaN" | ".NAN" if unsigned.len() == s.len() => return Some(f64::NAN),
+        _ => {
+            let starts_like_a_number = unsigned.starts_with(|c: char| c.is_ascii_digit())
+                || unsigned.starts_with('.')
+                    && unsigned[1..].starts_with(|c: char| c.is_ascii_digit());
+            if !starts_like_a_number
+                || !unsigned
+                    .bytes()
+                    .all(|b| b.is_ascii_digit() || matches!(b, b'.' | b'e' | b'E' | b'+' | b'-'))
+            {
+                return None;
+            }
+            unsigned.parse().ok()?
+        }
+    };
+    Some(if negative { -value } else { value })
+}
+
+/// YAML 1.1 base 60 floats such as `190:20:30.15`.
+pub(crate) fn parse_yaml11_float(s: &str) -> Option<f64> {
+    let (negative, digits) = split_sign(s);
+    let (whole, fraction) = digits.split_once('.')?;
+    if !whole.contains(':') {
+        return None;
+    }
+    let fraction: f64 = format!("0.{}", fraction.replace('_', "")).parse().ok()?;
+    let value = parse_base60(whole)? as f64 + fraction;
+    Some(if negative { -value } else { value })
+}
+
+fn parse_base60(s: &str) -> Option<i128> {
+    let mut parts = s.split(':');
+    let first = parts.next()?;
+    if first.starts_with('0') {
+        return None;
+    }
+    let mut value = digits_without_underscores(first, 10)?;
+    for part in parts {
+        if part.is_empty() || part.len() > 2 || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let sixtieths: i128 = part.parse().ok()?;
+        if sixtieths >= 60 {
+            return None;
+        }
+        value = value.checked_mul(60)?.checked_add(sixtieths)?;
+    }
+    Some(value)
+}
+
+/// Decodes the base64 body of a `!!binary` scalar, which may be split across lines.
+pub(crate) fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(s.len() / 4 * 3);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    let mut padding = 0;
+    for c in s.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => {
+                padding += 1;
+                continue;
+            }
+            _ => return None,
+        };
+        if padding > 0 {
+            return None;
+        }
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    // Leftover bits must be the zero padding of a final partial group.
+    (bits < 6 && buffer == 0 && padding <= 2).then_some(bytes)
+}
@@ -1,15 +1,28 @@
 use saphyr_parser::Event;
-use serde::de::{DeserializeSeed, SeqAccess};
+use serde::de::{DeserializeSeed, IgnoredAny, SeqAccess};
 
-use crate::{de::Deserializer, error::DeserializeError};
+use crate::{
+    de::{Deserializer, nested},
+    error::DeserializeError,
+};
 
 pub struct YamlSequence<'a, 'de: 'a> {
     de: &'a mut Deserializer<'de>,
+    index: usize,
 }
 
 impl<'a, 'de> YamlSequence<'a, 'de> {
     pub(crate) fn new(de: &'a mut Deserializer<'de>) -> Self {
-        Self { de }
+        Self { de, index: 0 }
+    }
+
+    /// Skips the elements the visitor left unread, returning how many there were.
+    pub(crate) fn skip_rest(&mut self) -> Result<usize, DeserializeError> {
+        let mut count = 0;
+        while self.next_element::<IgnoredAny>()?.is_some() {
+            count += 1;
+        }
+        Ok(count)
     }
 }
 
@@ -20,9 +33,53 @@ impl<'de, 'a> SeqAccess<'de> for YamlSequence<'a, 'de> {
     where
         T: DeserializeSeed<'de>,
     {
-        match self.de.peek_event() {
-            Some((Event::SequenceEnd, _span)) => Ok(None),
-            _ => seed.deserialize(&mut *self.de).map(Some),
+        if let Some((Event::SequenceEnd, _span)) = self.de.peek_event()? {
+            return Ok(None);
+        }
+        let index = self.index;
+        self.index += 1;
+        if !self.de.tracks_path() {
+            return nested(|| seed.deserialize(&mut *self.de))
+                .map(Some)
+                .map_err(|err| err.in_element(index));
+        }
+        self.de.path.push_index(index);
+        let value = self
+            .de
+            .enter_value()
+            .and_then(|()| nested(|| seed.deserialize(&mut *self.de)));
+        self.de.leave_value();
+        value.map(Some).map_err(|err| err.in_element(index))
+    }
+}
+
+/// The members of a `!!set`, which YAML writes as a mapping whose values are all null.
+pub struct YamlSet<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'a, 'de> YamlSet<'a, 'de> {
+    pub(crate) fn new(de: &'a mut Deserializer<'de>) -> Self {
+        Self { de }
+    }
+}
+
+impl<'de, 'a> SeqAccess<'de> for YamlSet<'a, 'de> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if let Some((Event::MappingEnd, _span)) = self.de.peek_event()? {
+            return Ok(None);
+        }
+        let member = seed.deserialize(&mut *self.de)?;
+        if !self.de.peek_null() {
+            let (event, span) = self.de.next_event()?;
+            return Err(self.de.mismatch("a null value", &event, span, "!!set"));
         }
+        self.de.next_event()?;
+        Ok(Some(member))
     }
 }
@@ -0,0 +1,45 @@
+//! Where each value came from, recorded by [`from_str_with_source_map`](crate::de::from_str_with_source_map).
+//!
+//! Paths use the syntax described in [`validate`](crate::validate), e.g. `server.tls.cert` or
+//! `containers[0].image`.
+
+use std::collections::BTreeMap;
+
+use saphyr_parser::Span;
+
+/// The source span of every mapping value and sequence element that was deserialized, keyed
+/// by path. A span covers the whole value, so for a nested mapping or sequence it runs to the
+/// end of its last entry.
+#[derive(Debug, Default, Clone)]
+pub struct SourceMap {
+    spans: BTreeMap<String, Span>,
+}
+
+impl SourceMap {
+    pub fn get(&self, path: &str) -> Option<Span> {
+        self.spans.get(path).copied()
+    }
+
+    /// Paths and spans in path order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Span)> {
+        self.spans.iter().map(|(path, span)| (path.as_str(), *span))
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    pub(crate) fn start(&mut self, path: &str, span: Span) {
+        self.spans.insert(String::from(path), span);
+    }
+
+    pub(crate) fn finish(&mut self, path: &str, end: saphyr_parser::Marker) {
+        if let Some(span) = self.spans.get_mut(path) {
+            span.end = end;
+        }
+    }
+}
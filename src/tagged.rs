@@ -0,0 +1,65 @@
+//! Reading a node together with its tag, see [`Tagged`].
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    Deserialize, Deserializer,
+    de::{EnumAccess, VariantAccess, Visitor},
+};
+
+/// The newtype struct name [`Tagged`] asks for, which the YAML deserializer answers with the tag
+/// and content of the next node.
+pub(crate) const TAGGED: &str = "$saphyr_serde::Tagged";
+
+/// A node's tag along with its content, for schemas where tags carry meaning, such as
+/// CloudFormation's `!Ref` and `!Sub`. The tag is written as in the source for local tags,
+/// `!Ref`, and as `!!int` for the core tags. Reading an untagged node fails.
+///
+/// ```
+/// use saphyr_serde::Tagged;
+///
+/// let bucket: Tagged<String> = saphyr_serde::from_str("!Ref LogsBucket").unwrap();
+/// assert_eq!(bucket.tag, "!Ref");
+/// assert_eq!(bucket.value, "LogsBucket");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Tagged<T> {
+    pub tag: String,
+    pub value: T,
+}
+
+impl<'de, T> Deserialize<'de> for Tagged<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(TAGGED, TaggedVisitor(PhantomData))
+    }
+}
+
+struct TaggedVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for TaggedVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Tagged<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a tagged node")
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Tagged<T>, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (tag, content) = data.variant()?;
+        Ok(Tagged {
+            tag,
+            value: content.newtype_variant()?,
+        })
+    }
+}
@@ -0,0 +1,80 @@
+//! Reading templated YAML, such as Helm charts or GitHub Actions workflows, before the templates
+//! are rendered. See [`TemplateOr`].
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    Deserialize, Deserializer,
+    de::{Error, Visitor},
+};
+
+/// The newtype struct name [`TemplateOr`] asks for, which the YAML deserializer answers with
+/// the scalar when it is a template expression.
+pub(crate) const TEMPLATE_OR: &str = "$saphyr_serde::TemplateOr";
+
+/// Either a template expression, `{{ ... }}` or `${{ ... }}`, kept as written, or a `T`, so a
+/// field can hold `"{{ .Values.port }}"` where a number is expected. Templates are only
+/// recognised with [`template_expressions`](crate::DeserializerOptions::template_expressions)
+/// enabled. An unquoted `{{ ... }}` is a flow mapping to a YAML parser, so it has to be quoted.
+///
+/// ```
+/// use saphyr_serde::{DeserializerOptions, TemplateOr};
+///
+/// let options = DeserializerOptions::new().template_expressions(true);
+/// let port: TemplateOr<u16> =
+///     saphyr_serde::from_str_with_options("'{{ .Values.port }}'", options.clone()).unwrap();
+/// assert_eq!(port, TemplateOr::Template("{{ .Values.port }}".into()));
+/// let port: TemplateOr<u16> = saphyr_serde::from_str_with_options("8080", options).unwrap();
+/// assert_eq!(port, TemplateOr::Value(8080));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TemplateOr<T> {
+    Template(String),
+    Value(T),
+}
+
+/// Whether `scalar` is a whole template expression.
+pub(crate) fn is_template(scalar: &str) -> bool {
+    let scalar = scalar.trim();
+    let inner = scalar.strip_prefix('$').unwrap_or(scalar);
+    inner.starts_with("{{") && inner.ends_with("}}") && inner.len() >= 4
+}
+
+impl<'de, T> Deserialize<'de> for TemplateOr<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(TEMPLATE_OR, TemplateOrVisitor(PhantomData))
+    }
+}
+
+struct TemplateOrVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for TemplateOrVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = TemplateOr<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a template expression or a value")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<TemplateOr<T>, E>
+    where
+        E: Error,
+    {
+        Ok(TemplateOr::Template(v.to_string()))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<TemplateOr<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(TemplateOr::Value)
+    }
+}
@@ -3,7 +3,11 @@ use std::f32;
 use serde::Deserialize;
 use serde_json::json;
 
-use crate::{de::from_str, error::DeserializeError};
+use crate::{
+    de::{Deserializer, from_str, from_str_with_options},
+    error::DeserializeError,
+    options::{DeserializerOptions, Schema, UnknownTags},
+};
 
 const ADDRESS_YAML_STR: &str = r###"
 street: Kerkstraat
@@ -188,6 +192,43 @@ value: ValueA
     );
 }
 
+#[test]
+fn it_reads_newtype_and_unit_variants_written_as_mappings() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum Shape {
+        Circle(f64),
+        Empty,
+    }
+
+    let result: Vec<Shape> = from_str("- Circle: 1.5\n- Empty: ~\n").expect("Should deserialize");
+    assert_eq!(result, vec![Shape::Circle(1.5), Shape::Empty]);
+    assert!(from_str::<Shape>("Empty: 3").is_err());
+}
+
+#[test]
+fn it_returns_errors_rather_than_panicking_on_malformed_input() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Config {
+        name: String,
+        tags: Vec<String>,
+    }
+
+    for input in [
+        "name: [",
+        "tags: {a",
+        "name: \"x",
+        "- [a, b\n",
+        "{name: x, tags: [a}",
+    ] {
+        assert!(from_str::<Config>(input).is_err(), "{input:?} should fail");
+        assert!(
+            from_str::<serde_json::Value>(input).is_err(),
+            "{input:?} should fail"
+        );
+    }
+}
+
 #[test]
 fn it_reads_externally_tagged_enums() {
     #[derive(Deserialize, PartialEq, Eq, Debug)]
@@ -308,6 +349,20 @@ double: 0.123
     assert!(are_nearly_equal(result.double, 0.123, f64::EPSILON));
 }
 
+#[test]
+fn it_reads_yaml_infinities_and_nan_as_floats() {
+    assert_eq!(from_str::<f64>(".inf").unwrap(), f64::INFINITY);
+    assert_eq!(from_str::<f64>("-.Inf").unwrap(), f64::NEG_INFINITY);
+    assert_eq!(from_str::<f32>("+.INF").unwrap(), f32::INFINITY);
+    assert!(from_str::<f64>(".nan").unwrap().is_nan());
+
+    // Rust's own spellings aren't YAML floats.
+    for input in ["inf", "-infinity", "NaN"] {
+        let err = from_str::<f64>(input).expect_err("Should not deserialize");
+        assert!(matches!(err, DeserializeError::NumberParseError { .. }));
+    }
+}
+
 #[test]
 fn it_reads_chars() {
     #[derive(Deserialize, Debug)]
@@ -443,9 +498,2004 @@ fn it_reads_internally_tagged_enums() {
 
     assert_eq!(
         err,
-        // ("unknown variant `UnknownVariant`, expected `Request` or `Response`")
-        DeserializeError::SerdeError(String::from(
-            "unknown variant `UnknownVariant`, expected `Request` or `Response`"
-        ))
+        DeserializeError::UnknownVariant {
+            variant: String::from("UnknownVariant"),
+            expected: &["Request", "Response"],
+            suggestion: None,
+            span: None,
+        }
+    );
+}
+
+#[test]
+fn it_reads_bools_with_the_yaml_1_2_schema() {
+    #[derive(Deserialize, Debug)]
+    struct Test {
+        b: bool,
+    }
+
+    let options = DeserializerOptions::new().schema(Schema::Yaml12);
+
+    let result: Test =
+        from_str_with_options("b: false", options.clone()).expect("Should deserialize");
+    assert!(!result.b);
+
+    from_str_with_options::<Test>("b: yes", options.clone()).expect_err("Should not deserialize");
+    from_str_with_options::<Test>("b: on", options).expect_err("Should not deserialize");
+}
+
+#[test]
+fn it_rejects_duplicate_keys_when_asked() {
+    use std::collections::HashMap;
+
+    const DUPLICATE_KEYS_YAML: &str = "a: 1\nb: 2\na: 3\n";
+
+    let result: HashMap<String, i32> = from_str(DUPLICATE_KEYS_YAML).expect("Should deserialize");
+    assert_eq!(result["a"], 3);
+
+    let err = from_str_with_options::<HashMap<String, i32>>(
+        DUPLICATE_KEYS_YAML,
+        DeserializerOptions::new().deny_duplicate_keys(true),
+    )
+    .expect_err("Should not deserialize");
+
+    assert!(matches!(err, DeserializeError::DuplicateKey { key, .. } if key == "a"));
+}
+
+#[test]
+fn it_limits_nesting_depth() {
+    let options = DeserializerOptions::new().max_depth(2);
+
+    let _value: Vec<Vec<i32>> =
+        from_str_with_options("- - 1\n  - 2\n", options.clone()).expect("Should deserialize");
+
+    let err = from_str_with_options::<Vec<Vec<Vec<i32>>>>("- - - 1\n", options)
+        .expect_err("Should not deserialize");
+
+    assert!(matches!(
+        err,
+        DeserializeError::DepthLimitExceeded { limit: 2, .. }
+    ));
+}
+
+#[test]
+fn it_applies_the_strict_preset() {
+    #[derive(Deserialize, Debug)]
+    struct Test {
+        b: bool,
+    }
+
+    let result: Test = from_str_with_options("b: true", DeserializerOptions::strict())
+        .expect("Should deserialize");
+    assert!(result.b);
+
+    from_str_with_options::<Test>("b: y", DeserializerOptions::strict())
+        .expect_err("Should not deserialize");
+    from_str_with_options::<Test>("b: true\nb: false", DeserializerOptions::strict())
+        .expect_err("Should not deserialize");
+    from_str_with_options::<Test>("b: true\n---\nb: false", DeserializerOptions::strict())
+        .expect_err("Should not deserialize");
+    from_str_with_options::<Test>("b: !flag true", DeserializerOptions::strict())
+        .expect_err("Should not deserialize");
+
+    let deep = "[".repeat(200) + &"]".repeat(200);
+    let err = from_str_with_options::<serde_json::Value>(&deep, DeserializerOptions::strict())
+        .expect_err("Should not deserialize");
+    assert!(matches!(err, DeserializeError::DepthLimitExceeded { .. }));
+}
+
+#[test]
+fn it_enforces_resource_limits() {
+    use crate::error::Limit;
+
+    let err = from_str_with_options::<Vec<i32>>(
+        "- 1\n- 2\n- 3\n",
+        DeserializerOptions::new().max_nodes(3),
+    )
+    .expect_err("Should not deserialize");
+    assert!(matches!(
+        err,
+        DeserializeError::LimitExceeded {
+            limit: Limit::Nodes,
+            max: 3,
+            ..
+        }
+    ));
+
+    let err = from_str_with_options::<Address>(
+        ADDRESS_YAML_STR,
+        DeserializerOptions::new().max_scalar_length(10),
+    )
+    .expect_err("Should not deserialize");
+    assert!(matches!(
+        err,
+        DeserializeError::LimitExceeded {
+            limit: Limit::ScalarLength,
+            ..
+        }
+    ));
+
+    let err = from_str_with_options::<Address>(
+        ADDRESS_YAML_STR,
+        DeserializerOptions::new().max_input_size(16),
+    )
+    .expect_err("Should not deserialize");
+    assert!(matches!(
+        err,
+        DeserializeError::LimitExceeded {
+            limit: Limit::InputSize,
+            ..
+        }
+    ));
+
+    let _value: Address = from_str_with_options(
+        ADDRESS_YAML_STR,
+        DeserializerOptions::new()
+            .max_nodes(5)
+            .max_scalar_length(13)
+            .max_input_size(ADDRESS_YAML_STR.len()),
+    )
+    .expect("Should deserialize");
+
+    // An endless reader stops being read once it passes the limit.
+    let err = crate::from_reader_with_options::<_, Address>(
+        std::io::repeat(b'#'),
+        DeserializerOptions::new().max_input_size(1024),
+    )
+    .expect_err("Should not deserialize");
+    assert!(matches!(
+        err,
+        DeserializeError::LimitExceeded {
+            limit: Limit::InputSize,
+            max: 1024,
+            ..
+        }
+    ));
+
+    let err = crate::from_str_with_source_map_with_options::<Vec<i32>>(
+        "- 1\n- 2\n- 3\n",
+        DeserializerOptions::new().max_nodes(3),
+    )
+    .expect_err("Should not deserialize");
+    assert!(matches!(
+        err,
+        DeserializeError::LimitExceeded {
+            limit: Limit::Nodes,
+            ..
+        }
+    ));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn it_transcodes_to_json_with_options() {
+    use crate::{error::Limit, json::transcode_to_json_with_options};
+
+    let mut json = Vec::new();
+    transcode_to_json_with_options(
+        "enabled: yes",
+        &mut json,
+        DeserializerOptions::new().legacy_1_1(true),
+    )
+    .expect("Should transcode");
+    assert_eq!(json, br#"{"enabled":true}"#);
+
+    let mut json = Vec::new();
+    let err = transcode_to_json_with_options(
+        ADDRESS_YAML_STR,
+        &mut json,
+        DeserializerOptions::new().max_input_size(16),
+    )
+    .expect_err("Should not transcode");
+    assert!(matches!(
+        err,
+        DeserializeError::LimitExceeded {
+            limit: Limit::InputSize,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn it_reads_yaml_1_1_scalars_in_legacy_mode() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Legacy {
+        enabled: bool,
+        mode: u32,
+        duration: i64,
+        angle: f64,
+    }
+
+    const LEGACY_YAML: &str = "enabled: yes\nmode: 0755\nduration: 1:30:00\nangle: -1:30.5\n";
+
+    let result: Legacy =
+        from_str_with_options(LEGACY_YAML, DeserializerOptions::new().legacy_1_1(true))
+            .expect("Should deserialize");
+    assert_eq!(
+        result,
+        Legacy {
+            enabled: true,
+            mode: 0o755,
+            duration: 5400,
+            angle: -90.5,
+        }
+    );
+
+    from_str::<Legacy>(LEGACY_YAML).expect_err("Should not deserialize");
+
+    let err = from_str_with_options::<u8>("0777", DeserializerOptions::new().legacy_1_1(true))
+        .expect_err("Should not deserialize");
+    assert!(matches!(err, DeserializeError::NumberParseError { .. }));
+}
+
+#[test]
+fn it_matches_enum_variants_case_insensitively_when_asked() {
+    #[derive(Deserialize, PartialEq, Eq, Debug)]
+    enum TestEnum {
+        ValueA,
+        ValueB { id: u32 },
+    }
+
+    from_str::<TestEnum>("valuea").expect_err("Should not deserialize");
+
+    let options = DeserializerOptions::new().case_insensitive_enums(true);
+    for yaml in ["valuea", "VALUEA", "value-a", "value_a"] {
+        let result: TestEnum =
+            from_str_with_options(yaml, options.clone()).expect("Should deserialize");
+        assert_eq!(result, TestEnum::ValueA);
+    }
+
+    let result: TestEnum =
+        from_str_with_options("value_b:\n  id: 7\n", options.clone()).expect("Should deserialize");
+    assert_eq!(result, TestEnum::ValueB { id: 7 });
+
+    from_str_with_options::<TestEnum>("value_c", options).expect_err("Should not deserialize");
+}
+
+#[test]
+fn it_renders_errors_with_source_snippets() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Test {
+        name: String,
+        count: u32,
+    }
+
+    const BAD_COUNT_YAML: &str = "name: widget\ncount: lots\n";
+
+    let err = from_str::<Test>(BAD_COUNT_YAML).expect_err("Should not deserialize");
+
+    assert_eq!(
+        err.display_with_source(BAD_COUNT_YAML).to_string(),
+        format!("{err}\n  |\n2 | count: lots\n  |        ^~~~")
+    );
+
+    let err = from_str::<Test>("name: widget\n").expect_err("Should not deserialize");
+    assert_eq!(
+        err.display_with_source("name: widget\n").to_string(),
+        err.to_string()
+    );
+}
+
+#[cfg(feature = "miette")]
+#[test]
+fn it_labels_miette_diagnostics() {
+    use miette::Diagnostic;
+
+    let err = from_str::<u32>("lots").expect_err("Should not deserialize");
+
+    assert_eq!(
+        err.code().map(|code| code.to_string()),
+        Some(String::from("saphyr_serde::number"))
+    );
+    assert!(err.help().is_some());
+    assert_eq!(
+        err.with_source_code("lots")
+            .labels()
+            .expect("Should have a label")
+            .count(),
+        1
+    );
+
+    // "à" is two bytes, so the label starts a byte after the column.
+    let input = "città: lots";
+    let err = from_str::<std::collections::HashMap<String, u32>>(input).expect_err("Not a number");
+    assert_eq!(err.column(), Some(7));
+    let diagnostic = err.with_source_code(input);
+    let label = diagnostic.labels().unwrap().next().unwrap();
+    assert_eq!(&input[label.offset()..label.offset() + label.len()], "lots");
+}
+
+#[test]
+fn it_reports_error_locations() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Test {
+        name: String,
+        count: u32,
+    }
+
+    let err = from_str::<Test>("name: widget\ncount: lots\n").expect_err("Should not deserialize");
+    assert_eq!(err.line(), Some(2));
+    assert_eq!(err.column(), Some(7));
+    assert!(err.to_string().contains("line 2, column 7"));
+
+    let err = from_str::<Test>("name: [widget\n").expect_err("Should not deserialize");
+    assert!(err.span().is_some());
+
+    let err = from_str::<Test>("name: widget\n").expect_err("Should not deserialize");
+    assert_eq!(err.span(), None);
+    assert_eq!(err.line(), None);
+}
+
+#[test]
+fn it_classifies_errors() {
+    use crate::error::ErrorKind;
+
+    let kind = |yaml: &str| {
+        from_str::<Address>(yaml)
+            .expect_err("Should not deserialize")
+            .kind()
+    };
+
+    assert_eq!(kind("street:\n  - Kerkstraat\n"), ErrorKind::Type);
+    assert_eq!(kind("street: Kerkstraat\n"), ErrorKind::Data);
+
+    let err = from_str::<String>("\"Kerkstraat").expect_err("Should not deserialize");
+    assert_eq!(err.kind(), ErrorKind::Syntax);
+
+    let err =
+        from_str_with_options::<Vec<Vec<i32>>>("- - 1\n", DeserializerOptions::new().max_depth(1))
+            .expect_err("Should not deserialize");
+    assert_eq!(err.kind(), ErrorKind::Limit);
+}
+
+#[test]
+fn it_reads_from_readers() {
+    use std::error::Error;
+
+    use crate::{de::from_reader, error::ErrorKind};
+
+    let result: Address = from_reader(ADDRESS_YAML_STR.as_bytes()).expect("Should deserialize");
+    assert_eq!(result.street, "Kerkstraat");
+
+    struct FailingReader;
+
+    impl std::io::Read for FailingReader {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("connection reset"))
+        }
+    }
+
+    let err = from_reader::<_, Address>(FailingReader).expect_err("Should not deserialize");
+    assert_eq!(err.kind(), ErrorKind::Io);
+    let io_error = err
+        .source()
+        .and_then(|source| source.source())
+        .and_then(|source| source.downcast_ref::<std::io::Error>())
+        .expect("Should chain the io::Error");
+    assert_eq!(io_error.to_string(), "connection reset");
+}
+
+#[test]
+fn it_locates_unknown_fields_and_suggests_alternatives() {
+    #[derive(Deserialize, Debug)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Test {
+        name: String,
+        count: u32,
+    }
+
+    let err = from_str::<Test>("nmae: widget\ncount: 3\n").expect_err("Should not deserialize");
+    assert!(matches!(
+        &err,
+        DeserializeError::UnknownField {
+            field,
+            suggestion: Some("name"),
+            span: Some(_),
+            ..
+        } if &**field == "nmae"
+    ));
+    assert_eq!(err.line(), Some(1));
+    assert_eq!(
+        err.to_string(),
+        "Unknown field `nmae`, did you mean `name`? at line 1, column 0"
+    );
+
+    let err = from_str::<Test>("name: widget\ncount: 3\ncolour: red\n")
+        .expect_err("Should not deserialize");
+    assert!(matches!(
+        err,
+        DeserializeError::UnknownField {
+            suggestion: None,
+            ..
+        }
+    ));
+    assert_eq!(err.line(), Some(3));
+}
+
+#[test]
+fn it_serializes_errors_as_diagnostics() {
+    let err = from_str::<u32>("lots").expect_err("Should not deserialize");
+
+    assert_eq!(
+        serde_json::to_value(&err).expect("Should serialize"),
+        json!({
+            "kind": "type",
+            "message": err.to_string(),
+            "line": 1,
+            "column": 0,
+            "path": null,
+        })
+    );
+
+    let err = from_str::<Address>("street: Kerkstraat\n").expect_err("Should not deserialize");
+    assert_eq!(
+        serde_json::to_value(&err).expect("Should serialize"),
+        json!({
+            "kind": "data",
+            "message": "Error during deserialization: missing field `state`",
+            "line": null,
+            "column": null,
+            "path": null,
+        })
+    );
+
+    #[derive(Deserialize, Debug)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Server {
+        port: u16,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Config {
+        servers: Vec<Server>,
+    }
+
+    let err = from_str::<Config>("servers:\n  - port: [80]\n").expect_err("Should not deserialize");
+    assert_eq!(
+        serde_json::to_value(&err).expect("Should serialize")["path"],
+        json!("servers[0].port")
+    );
+
+    let err = from_str::<Config>("servers:\n  - port: 80\n    hots: a\n")
+        .expect_err("Should not deserialize");
+    assert_eq!(
+        serde_json::to_value(&err).expect("Should serialize")["path"],
+        json!("servers[0].hots")
+    );
+}
+
+#[test]
+fn it_includes_recent_events_in_unexpected_element_errors() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Test {
+        name: String,
+        env: Vec<String>,
+    }
+
+    let err = from_str::<Test>("env: [a]\nname: widget\n---\nname: other\n")
+        .expect_err("Should not deserialize");
+
+    let DeserializeError::UnexpectedElement { recent_events, .. } = &err else {
+        panic!("Expected an unexpected element error, got {err:?}");
+    };
+    assert_eq!(recent_events.len(), 3);
+    assert!(recent_events.first().unwrap().contains("\"widget\""));
+    assert!(format!("{err:?}").contains("DocumentEnd"));
+}
+
+#[test]
+fn it_names_the_expected_and_found_kinds_of_value() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Server {
+        ports: Vec<u16>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Config {
+        servers: Vec<Server>,
+    }
+
+    let err = from_str::<Config>("servers:\n  - ports: [80]\n  - ports:\n      http: 80\n")
+        .expect_err("Should not deserialize");
+    assert_eq!(
+        err.to_string(),
+        "Expected a sequence for `servers[1].ports`, found a mapping at line 4, column 6"
+    );
+
+    let err = from_str::<Config>("servers: [80]").expect_err("Should not deserialize");
+    assert_eq!(
+        err,
+        DeserializeError::TypeMismatch {
+            expected: "a mapping",
+            found: "a scalar",
+            path: String::from("servers[0]"),
+            span: err.span().unwrap(),
+        }
+    );
+}
+
+#[test]
+fn it_names_keys_copied_by_the_parser_in_error_paths() {
+    use std::collections::BTreeMap;
+
+    // The key is only found again in the input for the error, so it is named as written.
+    let err = from_str::<BTreeMap<String, Vec<u32>>>("'it''s': {a: 1}\n")
+        .expect_err("Should not deserialize");
+    assert_eq!(
+        err.to_string(),
+        "Expected a sequence for `it''s`, found a mapping at line 1, column 9"
+    );
+}
+
+#[test]
+fn it_reads_prefixed_and_separated_ints() {
+    #[derive(Deserialize, PartialEq, Eq, Debug)]
+    struct TestInts {
+        hex: u32,
+        octal: u16,
+        binary: u8,
+        separated: i64,
+        negative_hex: i32,
+    }
+
+    let result: TestInts = from_str(
+        "hex: 0xFF\noctal: 0o755\nbinary: 0b1010\nseparated: 1_000_000\nnegative_hex: -0x10\n",
+    )
+    .expect("Should deserialize");
+    assert_eq!(
+        result,
+        TestInts {
+            hex: 255,
+            octal: 0o755,
+            binary: 10,
+            separated: 1_000_000,
+            negative_hex: -16,
+        }
+    );
+
+    let message = |yaml: &str| match from_str::<u8>(yaml).expect_err("Should not deserialize") {
+        DeserializeError::NumberParseError { err, .. } => err,
+        err => panic!("Expected a number parse error, got {err:?}"),
+    };
+    assert_eq!(message("12a"), "invalid digit `a` at position 2");
+    assert_eq!(message("0b102"), "invalid digit `2` at position 4");
+    assert_eq!(message("256"), "number too large to fit in target type");
+    assert_eq!(message("-1"), "number too small to fit in target type");
+    assert_eq!(message("0x"), "cannot parse integer from empty string");
+}
+
+#[test]
+fn it_reads_strings_into_cow_fields() {
+    use std::borrow::Cow;
+
+    #[derive(Deserialize, Debug)]
+    struct Strings<'a> {
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+        quoted: String,
+    }
+
+    // The parser hands out owned text, so even a `#[serde(borrow)]` field ends up owning it.
+    let result: Strings =
+        from_str("name: widget\nquoted: \"line\\none\"\n").expect("Should deserialize");
+    assert!(matches!(&result.name, Cow::Owned(name) if name == "widget"));
+    assert_eq!(result.quoted, "line\none");
+}
+
+#[test]
+fn it_reuses_a_deserializer_after_reset() {
+    let messages = ["x: 1\n", "x: 2\ny: 3\n", "x: 4\ny: 5\nz: 6\n"];
+    let mut deserializer =
+        Deserializer::from_str_with_options(messages[0], DeserializerOptions::new().max_nodes(5));
+
+    let read = |deserializer: &mut Deserializer| -> Result<Vec<i32>, DeserializeError> {
+        deserializer.start_stream()?;
+        deserializer.start_document()?;
+        let values = std::collections::BTreeMap::<String, i32>::deserialize(&mut *deserializer)?;
+        Ok(values.into_values().collect())
+    };
+
+    assert_eq!(
+        read(&mut deserializer).expect("Should deserialize"),
+        vec![1]
+    );
+
+    deserializer.reset(messages[1]);
+    assert_eq!(
+        read(&mut deserializer).expect("Should deserialize"),
+        vec![2, 3]
+    );
+
+    deserializer.reset(messages[2]);
+    let err = read(&mut deserializer).expect_err("Should not deserialize");
+    assert!(matches!(
+        err,
+        DeserializeError::LimitExceeded { max: 5, .. }
+    ));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn it_reads_yaml_timestamps_into_chrono_types() {
+    use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+
+    #[derive(Deserialize, Debug)]
+    struct Times {
+        #[serde(with = "crate::with::chrono::datetime_utc")]
+        spaced: DateTime<Utc>,
+        #[serde(with = "crate::with::chrono::datetime_utc")]
+        canonical: DateTime<Utc>,
+        #[serde(with = "crate::with::chrono::datetime_fixed_offset")]
+        offset: DateTime<FixedOffset>,
+        #[serde(with = "crate::with::chrono::naive_date")]
+        date: NaiveDate,
+        #[serde(with = "crate::with::chrono::naive_time")]
+        time: NaiveTime,
+    }
+
+    let times: Times = from_str(
+        "spaced: 2001-12-14 21:59:43.10 -5\ncanonical: 2001-12-15T02:59:43.1Z\noffset: 2001-12-14t21:59:43.10-05:00\ndate: 2002-12-14\ntime: 07:05:09.5\n",
+    )
+    .expect("Should deserialize");
+    let expected = NaiveDate::from_ymd_opt(2001, 12, 15)
+        .unwrap()
+        .and_hms_milli_opt(2, 59, 43, 100)
+        .unwrap();
+    assert_eq!(times.spaced, Utc.from_utc_datetime(&expected));
+    assert_eq!(times.canonical, times.spaced);
+    assert_eq!(times.offset, times.spaced);
+    assert_eq!(
+        times.offset.offset(),
+        &FixedOffset::west_opt(5 * 3600).unwrap()
+    );
+    assert_eq!(times.date, NaiveDate::from_ymd_opt(2002, 12, 14).unwrap());
+    assert_eq!(
+        times.time,
+        NaiveTime::from_hms_milli_opt(7, 5, 9, 500).unwrap()
+    );
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Date(#[serde(with = "crate::with::chrono::naive_date")] NaiveDate);
+    assert!(from_str::<Date>("2002-02-30").is_err());
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn it_reads_yaml_timestamps_into_time_types() {
+    use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+    #[derive(Deserialize, Debug)]
+    struct Times {
+        #[serde(with = "crate::with::time::offset_datetime")]
+        spaced: OffsetDateTime,
+        #[serde(with = "crate::with::time::date")]
+        date: Date,
+        #[serde(with = "crate::with::time::time_of_day")]
+        time: Time,
+        #[serde(with = "crate::with::time::duration")]
+        seconds: Duration,
+        #[serde(with = "crate::with::time::duration")]
+        sexagesimal: Duration,
+        #[serde(with = "crate::with::time::duration")]
+        fractional: Duration,
+    }
+
+    let times: Times = from_str(
+        "spaced: 2001-12-14 21:59:43.10 -5\ndate: 2002-12-14\ntime: 07:05:09.5\nseconds: 090\nsexagesimal: 1:30:00\nfractional: 0.25\n",
+    )
+    .expect("Should deserialize");
+    let expected = PrimitiveDateTime::new(
+        Date::from_calendar_date(2001, Month::December, 14).unwrap(),
+        Time::from_hms_milli(21, 59, 43, 100).unwrap(),
+    )
+    .assume_offset(UtcOffset::from_hms(-5, 0, 0).unwrap());
+    assert_eq!(times.spaced, expected);
+    assert_eq!(
+        times.spaced.offset(),
+        UtcOffset::from_hms(-5, 0, 0).unwrap()
+    );
+    assert_eq!(
+        times.date,
+        Date::from_calendar_date(2002, Month::December, 14).unwrap()
+    );
+    assert_eq!(times.time, Time::from_hms_milli(7, 5, 9, 500).unwrap());
+    assert_eq!(times.seconds, Duration::seconds(90));
+    assert_eq!(times.sexagesimal, Duration::seconds(5400));
+    assert_eq!(times.fractional, Duration::milliseconds(250));
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn it_keeps_key_order_in_index_maps() {
+    use indexmap::IndexMap;
+
+    #[derive(Deserialize, Debug)]
+    struct Config {
+        env: IndexMap<String, String>,
+        ports: IndexMap<u16, String>,
+    }
+
+    let config: Config = from_str(
+        "env:\n  ZEBRA: z\n  APPLE: a\n  MANGO: m\nports:\n  8080: http\n  22: ssh\n  443: https\n",
+    )
+    .expect("Should deserialize");
+    assert_eq!(
+        config.env.keys().collect::<Vec<_>>(),
+        ["ZEBRA", "APPLE", "MANGO"]
+    );
+    assert_eq!(config.ports.keys().collect::<Vec<_>>(), [&8080, &22, &443]);
+    assert_eq!(config.ports.get(&22).map(String::as_str), Some("ssh"));
+}
+
+#[test]
+fn it_reads_bytes_from_binary_scalars() {
+    struct ByteBuf(Vec<u8>);
+
+    impl<'de> Deserialize<'de> for ByteBuf {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            struct ByteBufVisitor;
+
+            impl<'de> serde::de::Visitor<'de> for ByteBufVisitor {
+                type Value = ByteBuf;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("bytes")
+                }
+
+                fn visit_bytes<E>(self, v: &[u8]) -> Result<ByteBuf, E> {
+                    Ok(ByteBuf(v.to_vec()))
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> Result<ByteBuf, A::Error>
+                where
+                    A: serde::de::SeqAccess<'de>,
+                {
+                    let mut bytes = Vec::new();
+                    while let Some(byte) = seq.next_element()? {
+                        bytes.push(byte);
+                    }
+                    Ok(ByteBuf(bytes))
+                }
+            }
+
+            deserializer.deserialize_byte_buf(ByteBufVisitor)
+        }
+    }
+
+    let binary: ByteBuf = from_str("!!binary |\n  R0lGODlh\n  AQAB\n").expect("Should decode");
+    assert_eq!(binary.0, b"GIF89a\x01\x00\x01");
+    let list: ByteBuf = from_str("[1, 2, 255]").expect("Should read a sequence");
+    assert_eq!(list.0, [1, 2, 255]);
+    let text: ByteBuf = from_str("plain text").expect("Should read the scalar's bytes");
+    assert_eq!(text.0, b"plain text");
+    assert!(matches!(
+        from_str::<ByteBuf>("!!binary not*base64"),
+        Err(DeserializeError::BinaryParseError { .. })
+    ));
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn it_reads_from_async_readers() {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use crate::{from_async_reader, from_async_reader_with_options};
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut context = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(result) = future.as_mut().poll(&mut context) {
+                break result;
+            }
+        }
+    }
+
+    let result = block_on(from_async_reader::<_, Address>(ADDRESS_YAML_STR.as_bytes()));
+    assert_eq!(result.expect("Should deserialize").street, "Kerkstraat");
+
+    // Input is decoded as `from_reader` decodes it.
+    let utf16le: Vec<u8> = "\u{feff}street: Kerkstraat\nstate: Noord Holland\n"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    let result = block_on(from_async_reader::<_, Address>(utf16le.as_slice()));
+    assert_eq!(result.expect("Should deserialize").state, "Noord Holland");
+
+    let result = block_on(from_async_reader_with_options::<_, Address>(
+        b"street: Kerk\xffstraat\nstate: Noord Holland\n".as_slice(),
+        DeserializerOptions::new().lossy_utf8(true),
+    ));
+    assert_eq!(
+        result.expect("Should deserialize").street,
+        "Kerk\u{fffd}straat"
+    );
+}
+
+#[cfg(feature = "flate2")]
+#[test]
+fn it_reads_gzipped_input() {
+    use std::io::Write;
+
+    use flate2::{Compression, write::GzEncoder};
+
+    use crate::de::{from_gzip_reader, from_reader};
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(ADDRESS_YAML_STR.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let address: Address = from_gzip_reader(compressed.as_slice()).expect("Should deserialize");
+    assert_eq!(address.street, "Kerkstraat");
+    let address: Address = from_reader(compressed.as_slice()).expect("Should deserialize");
+    assert_eq!(address.street, "Kerkstraat");
+
+    // The limit applies to the decompressed size.
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&[b'#'; 1 << 20]).unwrap();
+    let bomb = encoder.finish().unwrap();
+    let options = DeserializerOptions::new().max_input_size(1 << 16);
+    assert!(bomb.len() < 1 << 16);
+    let err = crate::from_reader_with_options::<_, Address>(bomb.as_slice(), options)
+        .expect_err("Should not deserialize");
+    assert_eq!(err.kind(), crate::ErrorKind::Limit);
+}
+
+#[test]
+fn it_reads_files_and_names_them_in_errors() {
+    use crate::{de::from_file, error::ErrorKind};
+
+    let dir = std::env::temp_dir().join(format!("saphyr-serde-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("address.yaml");
+    std::fs::write(&path, ADDRESS_YAML_STR).unwrap();
+    let address: Address = from_file(&path).expect("Should deserialize");
+    assert_eq!(address.street, "Kerkstraat");
+
+    std::fs::write(&path, "street: [Kerkstraat]").unwrap();
+    let err = from_file::<_, Address>(&path).expect_err("Should not deserialize");
+    assert!(
+        err.to_string()
+            .starts_with(&format!("{}: ", path.display()))
+    );
+    assert_eq!(err.kind(), ErrorKind::Type);
+    let missing = from_file::<_, Address>(dir.join("missing.yaml")).expect_err("Should fail");
+    assert_eq!(missing.kind(), ErrorKind::Io);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_reads_owned_input() {
+    use crate::de::from_string;
+
+    let body: Box<str> = ADDRESS_YAML_STR.into();
+    let address: Address = from_string(body).expect("Should deserialize");
+    assert_eq!(address.street, "Kerkstraat");
+}
+
+#[test]
+fn it_transcodes_utf16_and_utf32_readers() {
+    use crate::de::from_reader;
+
+    let utf16le: Vec<u8> = "\u{feff}street: Kerkstraat\nstate: Noord Holland\n"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    let utf32be: Vec<u8> = ADDRESS_YAML_STR
+        .chars()
+        .flat_map(|c| u32::from(c).to_be_bytes())
+        .collect();
+    for input in [utf16le, utf32be] {
+        let address: Address = from_reader(input.as_slice()).expect("Should deserialize");
+        assert_eq!(address.street, "Kerkstraat");
+        let address: Address = crate::from_slice_owned(&input).expect("Should deserialize");
+        assert_eq!(address.street, "Kerkstraat");
+        let err = crate::from_slice::<Address>(&input).expect_err("Should not borrow");
+        assert_eq!(err.kind(), crate::ErrorKind::Io);
+    }
+}
+
+#[test]
+fn it_optionally_replaces_invalid_utf8() {
+    use crate::de::{from_reader, from_reader_with_options};
+
+    let input = b"street: Kerk\xffstraat\nstate: Noord Holland\n";
+    assert!(from_reader::<_, Address>(&input[..]).is_err());
+    let address: Address =
+        from_reader_with_options(&input[..], DeserializerOptions::new().lossy_utf8(true))
+            .expect("Should deserialize");
+    assert_eq!(address.street, "Kerk\u{fffd}straat");
+
+    let warnings = crate::warning::Warnings::new();
+    let options = DeserializerOptions::new()
+        .lossy_utf8(true)
+        .warnings(warnings.clone());
+    let address: Address =
+        crate::from_slice_owned_with_options(input, options.clone()).expect("Should deserialize");
+    assert_eq!(address.street, "Kerk\u{fffd}straat");
+    assert_eq!(
+        warnings.take(),
+        [crate::warning::Warning::ReplacedInvalidEncoding { replacements: 1 }]
+    );
+    let err = crate::from_slice_with_options::<Address>(input, options).expect_err("Can't borrow");
+    assert_eq!(err.kind(), crate::ErrorKind::Io);
+}
+
+#[test]
+fn it_runs_validators_on_matching_paths() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Container {
+        image: String,
+        replicas: u32,
+    }
+
+    let input = "- image: nginx\n  replicas: 3\n- image: ''\n  replicas: 1\n";
+    let mut de = Deserializer::from_str(input);
+    de.add_validator("[*].image", |node| match node.scalar {
+        Some("") => Err(String::from("image must not be empty")),
+        _ => Ok(()),
+    });
+    de.start_stream().unwrap();
+    de.start_document().unwrap();
+    let err = Vec::<Container>::deserialize(&mut de).expect_err("Should reject the empty image");
+    assert_eq!(
+        err,
+        DeserializeError::ValidationFailed {
+            path: String::from("[1].image"),
+            message: String::from("image must not be empty"),
+            span: err.span().unwrap(),
+        }
+    );
+    assert_eq!((err.line(), err.column()), (Some(3), Some(9)));
+
+    let mut de = Deserializer::from_str(input);
+    de.add_validator("[0].replicas", |node| match node.scalar {
+        Some("3") => Ok(()),
+        _ => Err(String::from("expected three replicas")),
+    });
+    de.start_stream().unwrap();
+    de.start_document().unwrap();
+    assert_eq!(
+        Vec::<Container>::deserialize(&mut de)
+            .expect("Should deserialize")
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn it_records_a_source_map() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Tls {
+        cert: String,
+        key: String,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Server {
+        hosts: Vec<String>,
+        tls: Tls,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Config {
+        server: Server,
+    }
+
+    let input = "server:\n  hosts: [a, b]\n  tls:\n    cert: server.pem\n    key: server.key\n";
+    let (_config, source_map) =
+        crate::from_str_with_source_map::<Config>(input).expect("Should deserialize");
+    let cert = source_map
+        .get("server.tls.cert")
+        .expect("Should record cert");
+    assert_eq!((cert.start.line(), cert.start.col()), (4, 10));
+    assert_eq!(cert.end.col(), 20);
+    let host = source_map
+        .get("server.hosts[1]")
+        .expect("Should record hosts");
+    assert_eq!((host.start.line(), host.start.col()), (2, 13));
+    let tls = source_map.get("server.tls").expect("Should record tls");
+    assert_eq!((tls.start.line(), tls.end.line()), (4, 5));
+    assert_eq!(source_map.len(), 7);
+}
+
+#[test]
+fn it_reads_enum_map_keys() {
+    use std::collections::BTreeMap;
+
+    #[derive(Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug)]
+    enum Level {
+        Debug,
+        #[serde(rename = "warning")]
+        Warn,
+    }
+
+    let levels: BTreeMap<Level, u32> =
+        from_str("Debug: 1\nwarning: 2\n").expect("Should deserialize");
+    assert_eq!(
+        levels.into_iter().collect::<Vec<_>>(),
+        [(Level::Debug, 1), (Level::Warn, 2)]
+    );
+
+    let options = DeserializerOptions::new().case_insensitive_enums(true);
+    let levels: BTreeMap<Level, u32> =
+        from_str_with_options("debug: 1\n", options).expect("Should deserialize");
+    assert_eq!(levels[&Level::Debug], 1);
+
+    let err = from_str::<BTreeMap<Level, u32>>("Debug: 1\nInfo: 2\n").expect_err("Should fail");
+    assert_eq!((err.line(), err.column()), (Some(2), Some(0)));
+    assert_eq!(
+        err.to_string(),
+        "Unknown variant `Info` at line 2, column 0"
+    );
+}
+
+#[test]
+fn it_reads_numeric_map_keys() {
+    use std::collections::HashMap;
+
+    let names: HashMap<u32, String> =
+        from_str("1: one\n0x10: sixteen\n").expect("Should deserialize");
+    assert_eq!(names[&1], "one");
+    assert_eq!(names[&16], "sixteen");
+
+    let err = from_str::<HashMap<u8, String>>("1: one\n300: many\n").expect_err("Should fail");
+    assert!(matches!(err, DeserializeError::NumberParseError { .. }));
+    assert_eq!((err.line(), err.column()), (Some(2), Some(0)));
+}
+
+#[test]
+fn it_dumps_parser_events() {
+    let events = crate::debug::events("- a\n- b\n").expect("Should parse");
+    assert_eq!(events.len(), 8);
+    assert_eq!(
+        crate::debug::dump("[a]").to_string().lines().nth(3),
+        Some("1:1\tScalar(\"a\", Plain, 0, None)")
+    );
+    assert!(crate::debug::events("[a").is_err());
+    assert!(
+        crate::debug::dump("[a")
+            .to_string()
+            .lines()
+            .last()
+            .unwrap()
+            .starts_with("error: ")
+    );
+}
+
+#[test]
+fn it_reads_every_document_into_a_vec() {
+    let addresses: Vec<Address> = crate::from_str_all(
+        "street: Kerkstraat\nstate: Noord Holland\n---\nstreet: Dorpsstraat\nstate: Utrecht\n",
+    )
+    .expect("Should deserialize");
+    assert_eq!(addresses.len(), 2);
+    assert_eq!(addresses[1].street, "Dorpsstraat");
+
+    assert!(crate::from_str_all::<Address>("").unwrap().is_empty());
+    assert!(crate::from_str_all::<u8>("1\n---\nx\n").is_err());
+}
+
+#[test]
+fn it_reads_enums_written_as_tags() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum Message {
+        Request { id: String },
+        Retry(u32),
+        Pair(u32, u32),
+        Close,
+    }
+
+    let input = "- !Request {id: foo}\n- !Retry 3\n- !Pair [1, 2]\n- !Close\n- Close\n";
+    let options = DeserializerOptions::new().tagged_enums(true);
+    let messages: Vec<Message> = from_str_with_options(input, options).expect("Should deserialize");
+    assert_eq!(
+        messages,
+        [
+            Message::Request {
+                id: String::from("foo")
+            },
+            Message::Retry(3),
+            Message::Pair(1, 2),
+            Message::Close,
+            Message::Close,
+        ]
+    );
+
+    let err = from_str_with_options::<Message>(
+        "!Reqest {id: foo}",
+        DeserializerOptions::new().tagged_enums(true),
+    )
+    .expect_err("Should not deserialize");
+    assert!(matches!(
+        err,
+        DeserializeError::UnknownVariant {
+            suggestion: Some("Request"),
+            span: Some(_),
+            ..
+        }
+    ));
+    assert!(from_str::<Message>("!Retry 3").is_err());
+}
+
+#[test]
+fn it_reads_missing_optional_fields_as_none() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Server {
+        host: String,
+        port: Option<u16>,
+        tls: Option<bool>,
+        #[serde(default, with = "crate::with::double_option")]
+        proxy: Option<Option<String>>,
+    }
+
+    let server: Server = from_str("host: example.org\ntls: ~\n").expect("Should deserialize");
+    assert_eq!(
+        server,
+        Server {
+            host: String::from("example.org"),
+            port: None,
+            tls: None,
+            proxy: None,
+        }
+    );
+
+    let server: Server =
+        from_str("host: example.org\nport: 80\nproxy: null\n").expect("Should deserialize");
+    assert_eq!((server.port, server.proxy), (Some(80), Some(None)));
+}
+
+#[test]
+fn it_locates_sequences_of_the_wrong_length() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Host {
+        address: [u8; 4],
+    }
+
+    let err = from_str::<Host>("address: [127, 0, 1]").expect_err("Should be too short");
+    assert_eq!(
+        err.to_string(),
+        "Found 3 elements, expected an array of length 4 at line 1, column 9"
+    );
+
+    let err = from_str::<Host>("address:\n  - 127\n  - 0\n  - 0\n  - 1\n  - 2\n")
+        .expect_err("Should be too long");
+    assert!(matches!(
+        err,
+        DeserializeError::InvalidLength { found: 5, .. }
+    ));
+    assert_eq!((err.line(), err.column()), (Some(2), Some(2)));
+}
+
+#[test]
+fn it_never_reads_empty_scalars_as_booleans() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Flags {
+        flag: Option<bool>,
+        name: Option<String>,
+    }
+
+    for schema in [Schema::Yaml11, Schema::Yaml12] {
+        let options = DeserializerOptions::new().schema(schema);
+        let err = from_str_with_options::<bool>("''", options.clone()).expect_err("Should fail");
+        assert!(matches!(err, DeserializeError::BoolParseError { .. }));
+
+        let flags: Flags =
+            from_str_with_options("flag:\nname: ''\n", options).expect("Should deserialize");
+        assert_eq!(
+            flags,
+            Flags {
+                flag: None,
+                name: Some(String::new()),
+            }
+        );
+    }
+
+    let value: serde_json::Value = from_str("flag:").expect("Should deserialize");
+    assert_ne!(value["flag"], json!(true));
+}
+
+#[test]
+fn it_reads_str_tagged_nulls_as_strings() {
+    let read = |input| from_str::<Option<String>>(input).expect("Should deserialize");
+    assert_eq!(read("!!str null"), Some(String::from("null")));
+    assert_eq!(read("!!str ~"), Some(String::from("~")));
+    assert_eq!(read("!!str"), Some(String::new()));
+    assert_eq!(read("null"), None);
+
+    let values: Vec<Option<String>> = from_str("[!!str null, ~]").expect("Should deserialize");
+    assert_eq!(values, [Some(String::from("null")), None]);
+}
+
+#[test]
+fn it_reads_integers_with_a_leading_plus() {
+    assert_eq!(from_str::<u32>("+5").expect("Should deserialize"), 5);
+    assert_eq!(from_str::<i8>("+127").expect("Should deserialize"), 127);
+    assert_eq!(from_str::<u64>("+0x10").expect("Should deserialize"), 16);
+    assert_eq!(
+        from_str::<u64>("+18446744073709551615").expect("Should deserialize"),
+        u64::MAX
+    );
+    assert!(from_str::<u32>("+").is_err());
+    assert!(from_str::<i32>("+-5").is_err());
+}
+
+#[test]
+fn it_reads_and_writes_byte_sizes() {
+    #[derive(Deserialize, serde::Serialize, PartialEq, Debug)]
+    struct Limits {
+        #[serde(with = "crate::with::bytesize")]
+        memory: u64,
+    }
+
+    for (text, bytes) in [
+        ("1024", 1024),
+        ("512KiB", 512 * 1024),
+        ("10MB", 10_000_000),
+        ("1.5 GiB", 3 << 29),
+        ("2k", 2000),
+    ] {
+        let limits: Limits = from_str(&format!("memory: {text}")).expect("Should deserialize");
+        assert_eq!(limits.memory, bytes, "{text}");
+    }
+    for text in ["", "KiB", "10 XB", "-5", "1.2.3MB"] {
+        assert!(
+            from_str::<Limits>(&format!("memory: '{text}'")).is_err(),
+            "{text}"
+        );
+    }
+
+    let json = serde_json::to_value(Limits { memory: 512 * 1024 }).unwrap();
+    assert_eq!(json, json!({ "memory": "512KiB" }));
+    let json = serde_json::to_value(Limits { memory: 1000 }).unwrap();
+    assert_eq!(json, json!({ "memory": 1000 }));
+}
+
+#[test]
+fn it_resolves_plain_scalars_in_untyped_values() {
+    let value: serde_json::Value = from_str(
+        "null: ~\nempty:\nbool: true\nint: -12\nhex: 0x10\nbig: 18446744073709551615\nfloat: 1.5e3\n\
+         inf: -.inf\nword: inf\nquoted: '123'\ndouble: \"true\"\ntagged: !!str 7\nblock: |\n  8\n",
+    )
+    .expect("Should deserialize");
+    assert_eq!(
+        value,
+        json!({
+            "null": null,
+            "empty": null,
+            "bool": true,
+            "int": -12,
+            "hex": 16,
+            "big": u64::MAX,
+            "float": 1500.0,
+            "inf": null,
+            "word": "inf",
+            "quoted": "123",
+            "double": "true",
+            "tagged": "7",
+            "block": "8\n",
+        })
+    );
+
+    let legacy = DeserializerOptions::new().schema(Schema::Yaml11);
+    let value: serde_json::Value =
+        from_str_with_options("[yes, 0777, '0777']", legacy).expect("Should deserialize");
+    assert_eq!(value, json!([true, 511, "0777"]));
+}
+
+#[test]
+fn it_accepts_explicit_document_end_markers() {
+    let address: Address =
+        from_str("street: Kerkstraat\nstate: Noord Holland\n...\n").expect("Should deserialize");
+    assert_eq!(address.state, "Noord Holland");
+    assert_eq!(
+        from_str::<u8>("1\n...\n# done\n").expect("Should deserialize"),
+        1
+    );
+    assert_eq!(
+        from_str::<serde_json::Value>("...\n").expect("Should deserialize"),
+        serde_json::Value::Null
+    );
+    assert_eq!(
+        crate::from_str_all::<u8>("1\n...\n---\n2\n...\n").expect("Should deserialize"),
+        [1, 2]
+    );
+}
+
+#[test]
+fn it_deserializes_only_the_documents_a_predicate_selects() {
+    #[derive(Deserialize, Debug)]
+    struct Deployment {
+        replicas: u32,
+    }
+
+    let input = "kind: Deployment\nspec: {kind: Service}\nreplicas: 2\n---\n\
+                 kind: Service\nports: [{port: 80}]\n---\n[kind, Deployment]\n---\n\
+                 ...\n---\nkind: Deployment\nreplicas: 3\n";
+    let mut heads = Vec::new();
+    let deployments: Vec<Deployment> = Deserializer::from_str(input)
+        .filter_deserialize(|head| {
+            heads.push(head.iter().count());
+            head.get("kind") == Some("Deployment")
+        })
+        .collect::<Result<_, _>>()
+        .expect("Should deserialize");
+    assert_eq!(
+        deployments.iter().map(|d| d.replicas).collect::<Vec<_>>(),
+        [2, 3]
+    );
+    // A head stops at the first nested value, so `replicas: 2` isn't part of the first one.
+    assert_eq!(heads, [1, 1, 0, 0, 2]);
+
+    let mut documents = Deserializer::from_str("kind: a\n---\nkind: b\nn: x\n")
+        .filter_deserialize::<Deployment, _>(|head| head.get("kind") == Some("b"));
+    assert!(documents.next().unwrap().is_err());
+    assert!(documents.next().is_none());
+
+    // Limits apply to the heads, and the nodes read for a head aren't counted again.
+    let read = |max_nodes| {
+        Deserializer::from_str_with_options(
+            "kind: a\nn: [1, 2]\n",
+            DeserializerOptions::new().max_nodes(max_nodes),
+        )
+        .filter_deserialize::<serde::de::IgnoredAny, _>(|_head| true)
+        .next()
+        .unwrap()
+    };
+    assert!(read(7).is_ok());
+    assert!(matches!(
+        read(6),
+        Err(DeserializeError::LimitExceeded {
+            limit: crate::error::Limit::Nodes,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn it_reads_sets() {
+    use std::collections::{BTreeSet, HashSet};
+
+    let hosts: BTreeSet<String> =
+        from_str("!!set\nb.example.org:\na.example.org:\n").expect("Should deserialize");
+    assert_eq!(
+        hosts.into_iter().collect::<Vec<_>>(),
+        ["a.example.org", "b.example.org"]
+    );
+    let ports: HashSet<u16> = from_str("!!set {80: ~, 443: null}").expect("Should deserialize");
+    assert_eq!(ports, HashSet::from([80, 443]));
+    let ports: Vec<u16> = from_str("!!set {80, 443}").expect("Should deserialize");
+    assert_eq!(ports, [80, 443]);
+
+    let err = from_str::<HashSet<u16>>("!!set {80: yes}").expect_err("Should not deserialize");
+    assert!(matches!(
+        err,
+        DeserializeError::TypeMismatch {
+            expected: "a null value",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn it_applies_the_unknown_tag_policy() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum Shape {
+        Circle(u32),
+    }
+
+    let input = "- !!str 1\n- !Ref 2\n";
+    let ignored: Vec<String> = from_str(input).expect("Should deserialize");
+    assert_eq!(ignored, ["1", "2"]);
+
+    let deny = || DeserializerOptions::new().unknown_tags(UnknownTags::Error);
+    let err =
+        from_str_with_options::<Vec<String>>(input, deny()).expect_err("Should not deserialize");
+    assert!(matches!(err, DeserializeError::UnknownTag { ref tag, .. } if tag == "!Ref"));
+
+    let shapes: Vec<Shape> = from_str_with_options("- !Circle 2\n", deny().tagged_enums(true))
+        .expect("Should deserialize");
+    assert_eq!(shapes, [Shape::Circle(2)]);
+    assert!(from_str_with_options::<Vec<Shape>>("- !Circle !Ref 2\n", deny()).is_err());
+}
+
+#[test]
+fn it_optionally_reads_whole_floats_as_integers() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Deployment {
+        replicas: u32,
+    }
+
+    assert!(from_str::<Deployment>("replicas: 3.0").is_err());
+    let coerce = || DeserializerOptions::new().whole_floats_as_integers(true);
+    let deployment: Deployment =
+        from_str_with_options("replicas: 3.0", coerce()).expect("Should deserialize");
+    assert_eq!(deployment, Deployment { replicas: 3 });
+    for input in ["replicas: 3.5", "replicas: -1.0", "replicas: .inf"] {
+        assert!(from_str_with_options::<Deployment>(input, coerce()).is_err());
+    }
+}
+
+#[test]
+fn it_resumes_reading_at_a_byte_offset() {
+    use std::collections::HashMap;
+
+    let input = "first: 1\n...\nsecond: 2\n";
+    let mut documents = Deserializer::from_str(input).into_iter::<HashMap<String, u32>>();
+    let first = documents.next().unwrap().expect("Should deserialize");
+    assert_eq!(first["first"], 1);
+    let offset = documents.byte_offset();
+    assert_eq!(&input[..offset], "first: 1\n...");
+
+    let mut rest = Deserializer::from_str_at(input, offset)
+        .expect("Should start at a character boundary")
+        .into_iter::<HashMap<String, u32>>();
+    let second = rest.next().unwrap().expect("Should deserialize");
+    assert_eq!(second["second"], 2);
+    assert_eq!(rest.byte_offset(), input.len());
+
+    // Inside the two byte "ü", and past the end.
+    for offset in [2, 9] {
+        let err = Deserializer::from_str_at("Zürich", offset)
+            .err()
+            .expect("Should fail");
+        assert_eq!(err.kind(), crate::ErrorKind::Io);
+    }
+}
+
+#[test]
+fn it_skips_values_and_returns_their_spans() {
+    let input = "skip:\n  nested: [1, 2]\n  other: &x x\nalias: *x\nkeep: 3\n";
+    let mut deserializer = Deserializer::from_str(input);
+    deserializer.start_stream().unwrap();
+    deserializer.start_document().unwrap();
+    deserializer.start_map().unwrap();
+    let mut skipped = Vec::new();
+    for _ in 0..2 {
+        deserializer.skip_value().expect("Should skip the key");
+        let span = deserializer.skip_value().expect("Should skip the value");
+        skipped.push(&input[span.start.index()..span.end.index()]);
+    }
+    assert_eq!(skipped, ["nested: [1, 2]\n  other: &x x", "*x"]);
+    assert_eq!(deserializer.read_scalar_string().unwrap().0, "keep");
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn it_compares_yaml_ignoring_formatting() {
+    use crate::test_util::assert_yaml_eq;
+
+    assert_yaml_eq(
+        "name: web\nports:\n  - 80\n  - 443\n",
+        "{ports: [80, 443], 'name': \"web\"}",
+    );
+    let differ = |left: &'static str, right: &'static str| {
+        std::panic::catch_unwind(|| assert_yaml_eq(left, right)).is_err()
+    };
+    assert!(differ("port: 80", "port: '80'"));
+    assert!(differ("ports: [80, 443]", "ports: [443, 80]"));
+}
+
+#[test]
+fn it_checks_for_leftover_content_at_the_end() {
+    let input = "street: Kerkstraat\nstate: Noord Holland\n---\nstreet: Main Street\n";
+    let mut deserializer = Deserializer::from_str(input);
+    deserializer.start_stream().unwrap();
+    deserializer.start_document().unwrap();
+    let address = Address::deserialize(&mut deserializer).expect("Should deserialize");
+    assert_eq!(address.street, "Kerkstraat");
+    let err = deserializer
+        .end()
+        .expect_err("Should find the second document");
+    assert_eq!(err.line(), Some(3));
+
+    let mut deserializer = Deserializer::from_str(ADDRESS_YAML_STR);
+    deserializer.start_stream().unwrap();
+    deserializer.start_document().unwrap();
+    Address::deserialize(&mut deserializer).expect("Should deserialize");
+    deserializer.end().expect("Should be at the end");
+}
+
+#[test]
+fn it_rejects_input_with_more_than_one_document() {
+    for input in ["1\n---\n2\n", "1\n...\n---\n2\n"] {
+        let err = from_str::<u8>(input).expect_err("Should not deserialize");
+        assert_eq!(
+            err.line(),
+            Some(input.lines().position(|line| line == "---").unwrap() + 1)
+        );
+        assert!(crate::from_slice::<u8>(input.as_bytes()).is_err());
+    }
+}
+
+#[test]
+fn it_converts_between_markers_and_byte_offsets() {
+    use crate::position::{byte_offset, marker_at, slice};
+
+    let input = "città: Zürich\nstreet: Kerkstraat\n";
+    let (_event, span) = crate::debug::events(input).unwrap()[4].clone();
+    assert_eq!(slice(input, span), Some("Zürich"));
+    for offset in [0, 7, input.find("street").unwrap(), input.len()] {
+        let marker = marker_at(input, offset).expect("Should be a position in the input");
+        assert_eq!(byte_offset(input, marker), Some(offset));
+    }
+    assert_eq!(marker_at(input, 5), None);
+}
+
+#[test]
+fn it_reads_tags_into_tagged_values() {
+    use std::collections::BTreeMap;
+
+    use crate::Tagged;
+
+    let input = "bucket: !Ref LogsBucket\nname: !Sub [\"${Env}-logs\", {}]\nport: !!int 80\n";
+    let values: BTreeMap<String, Tagged<serde_json::Value>> = from_str_with_options(
+        input,
+        DeserializerOptions::new().unknown_tags(UnknownTags::Error),
+    )
+    .expect("Should deserialize");
+    assert_eq!(values["bucket"].tag, "!Ref");
+    assert_eq!(values["name"].value, json!(["${Env}-logs", {}]));
+    assert_eq!(
+        values["port"],
+        Tagged {
+            tag: "!!int".into(),
+            value: json!(80)
+        }
+    );
+
+    let err = from_str::<Tagged<String>>("LogsBucket").expect_err("Should not deserialize");
+    assert!(matches!(
+        err,
+        DeserializeError::TypeMismatch {
+            expected: "a tagged node",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn it_reports_how_far_reading_got() {
+    use std::collections::HashMap;
+
+    let input = "city: Zürich\n...\ncity: Genève\n";
+    let mut documents = Deserializer::from_str(input).into_iter::<HashMap<String, String>>();
+    documents.next().unwrap().expect("Should deserialize");
+    let position = documents.position();
+    assert_eq!((position.line(), position.col()), (2, 3));
+    assert_eq!(documents.byte_offset(), input.find("...").unwrap() + 3);
+}
+
+#[test]
+fn it_reports_each_value_read_to_a_callback() {
+    use std::{cell::RefCell, rc::Rc};
+
+    let input = "street: Kerkstraat\nstate: Noord Holland\nextra: [1, 2]\n";
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let mut deserializer = Deserializer::from_str(input);
+    let record = Rc::clone(&seen);
+    deserializer.on_value(move |path, span| {
+        record
+            .borrow_mut()
+            .push((path.to_string(), span.start.index()..span.end.index()))
+    });
+    deserializer.start_stream().unwrap();
+    deserializer.start_document().unwrap();
+    Address::deserialize(&mut deserializer).expect("Should deserialize");
+
+    let seen = seen.borrow();
+    let text: Vec<_> = seen
+        .iter()
+        .map(|(path, range)| (path.as_str(), &input[range.clone()]))
+        .collect();
+    assert_eq!(
+        text,
+        [
+            ("street", "Kerkstraat"),
+            ("state", "Noord Holland"),
+            ("extra[0]", "1"),
+            ("extra[1]", "2"),
+            ("extra", "[1, 2]"),
+        ]
+    );
+}
+
+#[test]
+fn it_reads_and_writes_integers_in_hex_and_octal() {
+    #[derive(Deserialize, serde::Serialize, PartialEq, Debug)]
+    struct Permissions {
+        #[serde(with = "crate::with::hex_int")]
+        flags: u8,
+        #[serde(with = "crate::with::octal_int")]
+        mode: u32,
+    }
+
+    let permissions: Permissions =
+        from_str("flags: '0x1F'\nmode: 0o755\n").expect("Should deserialize");
+    assert_eq!(
+        permissions,
+        Permissions {
+            flags: 31,
+            mode: 0o755
+        }
+    );
+    assert_eq!(
+        serde_json::to_value(&permissions).unwrap(),
+        json!({"flags": "0x1F", "mode": "0o755"})
+    );
+    let decimal: Permissions = from_str("flags: 31\nmode: 493\n").expect("Should deserialize");
+    assert_eq!(decimal, permissions);
+    assert!(from_str::<Permissions>("flags: 0x100\nmode: 0").is_err());
+
+    #[derive(Deserialize, serde::Serialize, PartialEq, Debug)]
+    struct Offsets {
+        #[serde(with = "crate::with::hex_int")]
+        delta: i8,
+        #[serde(with = "crate::with::octal_int")]
+        lowest: i64,
+    }
+
+    let offsets = Offsets {
+        delta: -1,
+        lowest: i64::MIN,
+    };
+    let written = serde_json::to_value(&offsets).unwrap();
+    assert_eq!(
+        written,
+        json!({"delta": "-0x1", "lowest": "-0o1000000000000000000000"})
+    );
+    let yaml = format!(
+        "delta: {}\nlowest: {}\n",
+        written["delta"], written["lowest"]
+    );
+    assert_eq!(
+        from_str::<Offsets>(&yaml).expect("Should read back"),
+        offsets
+    );
+}
+
+#[test]
+fn it_checks_every_document_and_reports_each_failure() {
+    let input = "street: Kerkstraat\nstate: Utrecht\n---\nstreet: [a, {b: c}]\nstate: x\n---\n\
+                 street: Dorpsstraat\nstate: Utrecht\n---\nstreet: Main Street\n";
+    let errors = crate::check::<Address>(input).expect_err("Should find two bad documents");
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].line(), Some(4));
+    assert_eq!(
+        errors[1].to_string(),
+        "Error during deserialization: missing field `state`"
+    );
+    assert_eq!(crate::check::<Address>(ADDRESS_YAML_STR), Ok(()));
+}
+
+#[test]
+fn it_keeps_unknown_variants_in_a_fallback() {
+    use crate::with::enum_fallback::Fallback;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum Shape {
+        Circle(u32),
+        Empty,
+        #[serde(skip_deserializing)]
+        Unknown(String, serde_json::Value),
+    }
+
+    impl Fallback for Shape {
+        type Body = serde_json::Value;
+
+        fn fallback(variant: String, body: serde_json::Value) -> Self {
+            Shape::Unknown(variant, body)
+        }
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Drawing {
+        #[serde(with = "crate::with::enum_fallback")]
+        shape: Shape,
+    }
+
+    let read = |input| from_str::<Drawing>(input).map(|drawing| drawing.shape);
+    assert_eq!(read("shape: {Circle: 2}").unwrap(), Shape::Circle(2));
+    assert_eq!(read("shape: Empty").unwrap(), Shape::Empty);
+    assert_eq!(
+        read("shape: {Square: [1, 2]}").unwrap(),
+        Shape::Unknown("Square".into(), json!([1, 2]))
+    );
+    assert_eq!(
+        read("shape: Dot").unwrap(),
+        Shape::Unknown("Dot".into(), serde_json::Value::Null)
+    );
+    assert!(read("shape: {Circle: x}").is_err());
+    assert!(
+        read("shape: {Unknown: [a, 1]}")
+            .is_ok_and(|shape| shape == Shape::Unknown("Unknown".into(), json!(["a", 1])))
+    );
+}
+
+#[test]
+fn it_keeps_template_expressions_where_values_are_expected() {
+    use crate::TemplateOr;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Service {
+        port: TemplateOr<u16>,
+        enabled: TemplateOr<bool>,
+        name: String,
+    }
+
+    let input =
+        "port: '{{ .Values.port }}'\nenabled: ${{ inputs.enabled }}\nname: '{{ .Release.Name }}'\n";
+    let options = DeserializerOptions::new().template_expressions(true);
+    assert_eq!(
+        from_str_with_options::<Service>(input, options.clone()).unwrap(),
+        Service {
+            port: TemplateOr::Template("{{ .Values.port }}".into()),
+            enabled: TemplateOr::Template("${{ inputs.enabled }}".into()),
+            name: "{{ .Release.Name }}".into(),
+        }
+    );
+    assert_eq!(
+        from_str_with_options::<Service>("port: 80\nenabled: true\nname: web", options)
+            .unwrap()
+            .port,
+        TemplateOr::Value(80)
+    );
+    assert!(from_str::<Service>(input).is_err());
+}
+
+#[test]
+fn it_expands_cloudformation_intrinsic_tags() {
+    let input = r#"
+BucketName: !Ref LogsBucket
+Arn: !GetAtt LogsBucket.Arn
+Url: !Sub "https://${LogsBucket}.s3.amazonaws.com"
+Az: !Select [0, !GetAZs ""]
+"#;
+    let options = DeserializerOptions::new()
+        .cloudformation_tags(true)
+        .unknown_tags(UnknownTags::Error);
+    let template: serde_json::Value = from_str_with_options(input, options).unwrap();
+    assert_eq!(
+        template,
+        json!({
+            "BucketName": {"Ref": "LogsBucket"},
+            "Arn": {"Fn::GetAtt": ["LogsBucket", "Arn"]},
+            "Url": {"Fn::Sub": "https://${LogsBucket}.s3.amazonaws.com"},
+            "Az": {"Fn::Select": [0, {"Fn::GetAZs": ""}]},
+        })
+    );
+
+    let plain: serde_json::Value = from_str("BucketName: !Ref LogsBucket").unwrap();
+    assert_eq!(plain, json!({"BucketName": "LogsBucket"}));
+}
+
+#[test]
+fn it_offers_a_serde_yaml_shaped_api() {
+    use crate::compat;
+
+    let address: Address =
+        compat::from_slice(ADDRESS_YAML_STR.as_bytes()).expect("Should deserialize");
+    assert_eq!(address.street, "Kerkstraat");
+
+    let err: compat::Error = compat::from_str::<u8>("\n  300").expect_err("Should not fit");
+    let location = err.location().expect("Should have a location");
+    assert_eq!((location.line(), location.column()), (2, 3));
+    assert_eq!(location.index(), 3);
+
+    // Like serde_yaml, the index counts bytes, and `à` takes two.
+    let input = "città: 300";
+    let err = compat::from_str::<std::collections::BTreeMap<String, u8>>(input)
+        .expect_err("Should not fit");
+    let location = err.location().expect("Should have a location");
+    assert_eq!((location.line(), location.column()), (1, 8));
+    assert_eq!(&input[location.index()..], "300");
+    let err = compat::from_reader::<_, std::collections::BTreeMap<String, u8>>(input.as_bytes())
+        .expect_err("Should not fit");
+    assert_eq!(err.location().map(|location| location.index()), Some(8));
+
+    assert!(compat::from_slice::<String>(b"\xff\xfe").is_err());
+}
+
+#[cfg(feature = "figment")]
+#[test]
+fn it_provides_yaml_to_figment() {
+    use figment::{Figment, providers::Format};
+
+    use crate::figment::Yaml;
+
+    let figment = Figment::from(Yaml::string(ADDRESS_YAML_STR));
+    let address: Address = figment.extract().expect("Should extract");
+    assert_eq!(address.state, "Noord Holland");
+}
+
+#[test]
+fn it_exposes_the_entry_points_at_the_crate_root() {
+    let address: Address = crate::from_str(ADDRESS_YAML_STR).expect("Should deserialize");
+    assert_eq!(address.street, "Kerkstraat");
+    let err: crate::DeserializeError = crate::from_slice::<u8>(b"x").expect_err("Not a number");
+    assert_eq!(err.kind(), crate::ErrorKind::Type);
+}
+
+#[test]
+fn it_applies_options_through_every_entry_point() {
+    use crate::de::{from_reader_with_options, from_slice_with_options};
+
+    let legacy = || DeserializerOptions::new().legacy_1_1(true);
+    assert!(from_slice_with_options::<bool>(b"yes", legacy()).expect("Should deserialize"));
+    assert!(from_reader_with_options::<_, bool>(&b"on"[..], legacy()).expect("Should deserialize"));
+    assert!(from_slice_with_options::<bool>(b"yes", DeserializerOptions::new()).is_err());
+}
+
+#[test]
+fn it_iterates_over_documents() {
+    let input =
+        "street: Kerkstraat\nstate: Noord Holland\n---\nstreet: Main Street\nstate: New York\n";
+    let addresses: Vec<Address> = Deserializer::from_str(input)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .expect("Should deserialize");
+    assert_eq!(addresses.len(), 2);
+    assert_eq!(addresses[1].street, "Main Street");
+
+    assert_eq!(Deserializer::from_str("").into_iter::<Address>().count(), 0);
+
+    let mut documents = Deserializer::from_str("1\n--- x\n--- 3\n").into_iter::<u8>();
+    assert_eq!(documents.next(), Some(Ok(1)));
+    assert!(matches!(documents.next(), Some(Err(_))));
+    assert_eq!(documents.next(), None);
+}
+
+#[test]
+fn it_reads_and_writes_enums_as_singleton_maps() {
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    enum Shape {
+        Point,
+        Square(f64),
+        Line(u8, u8),
+        Circle { radius: f64 },
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Drawing {
+        #[serde(with = "crate::with::singleton_map")]
+        first: Shape,
+        #[serde(with = "crate::with::singleton_map")]
+        second: Shape,
+        #[serde(with = "crate::with::singleton_map")]
+        third: Option<Shape>,
+        #[serde(with = "crate::with::singleton_map")]
+        fourth: Shape,
+    }
+
+    let drawing: Drawing = from_str(
+        "first: Point\nsecond:\n  Line: [1, 2]\nthird:\n  Circle:\n    radius: 1.5\nfourth: {Square: 2.0}\n",
+    )
+    .expect("Should deserialize");
+    assert_eq!(
+        drawing,
+        Drawing {
+            first: Shape::Point,
+            second: Shape::Line(1, 2),
+            third: Some(Shape::Circle { radius: 1.5 }),
+            fourth: Shape::Square(2.0),
+        }
+    );
+
+    let json = serde_json::to_string(&drawing).expect("Should serialize");
+    assert_eq!(
+        json,
+        r#"{"first":"Point","second":{"Line":[1,2]},"third":{"Circle":{"radius":1.5}},"fourth":{"Square":2.0}}"#
+    );
+    assert_eq!(
+        serde_json::from_str::<Drawing>(&json).expect("Should deserialize"),
+        drawing
+    );
+    assert!(
+        from_str::<Drawing>(
+            "first: {Point: ~, Line: [1, 2]}\nsecond: Point\nthird: ~\nfourth: Point\n"
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn it_reads_singleton_map_variants_named_like_other_scalars() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum Answer {
+        #[serde(rename = "true")]
+        Yes,
+        #[serde(rename = "null")]
+        Nothing,
+        #[serde(rename = "~")]
+        Tilde,
+        #[serde(rename = "1")]
+        One(u8),
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Form {
+        #[serde(with = "crate::with::singleton_map")]
+        answer: Answer,
+    }
+
+    let read = |input| from_str::<Form>(input).map(|form| form.answer);
+    assert_eq!(read("answer: true").unwrap(), Answer::Yes);
+    assert_eq!(read("answer: null").unwrap(), Answer::Nothing);
+    assert_eq!(read("answer: ~").unwrap(), Answer::Tilde);
+    assert_eq!(read("answer: {1: 5}").unwrap(), Answer::One(5));
+}
+
+#[test]
+fn it_reads_a_string_or_a_struct() {
+    use std::str::FromStr;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Endpoint {
+        host: String,
+        #[serde(default)]
+        tls: bool,
+    }
+
+    impl FromStr for Endpoint {
+        type Err = std::convert::Infallible;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Ok(Endpoint {
+                host: String::from(s),
+                tls: false,
+            })
+        }
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Config {
+        #[serde(with = "crate::with::string_or_struct")]
+        endpoint: Endpoint,
+    }
+
+    let short: Config = from_str("endpoint: example.com").expect("Should deserialize");
+    assert_eq!(short.endpoint.host, "example.com");
+    let long: Config =
+        from_str("endpoint:\n  host: example.com\n  tls: true\n").expect("Should deserialize");
+    assert_eq!(
+        long.endpoint,
+        Endpoint {
+            host: String::from("example.com"),
+            tls: true
+        }
     );
+    assert!(from_str::<Config>("endpoint: [example.com]").is_err());
+
+    // Scalars that resolve to numbers or booleans still go through `FromStr`.
+    let port: Config = from_str("endpoint: 8080").expect("Should deserialize");
+    assert_eq!(port.endpoint.host, "8080");
+    let version: Config = from_str("endpoint: 1.2").expect("Should deserialize");
+    assert_eq!(version.endpoint.host, "1.2");
+    let flag: Config = from_str("endpoint: true").expect("Should deserialize");
+    assert_eq!(flag.endpoint.host, "true");
+    let negative: Config = from_str("endpoint: -1").expect("Should deserialize");
+    assert_eq!(negative.endpoint.host, "-1");
 }
@@ -0,0 +1,147 @@
+// The YAML 1.1 timestamp format (https://yaml.org/type/timestamp.html), parsed into fields that
+// the date and time adapters in `crate::with` build their own types from.
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Timestamp {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub time: Option<TimeOfDay>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct TimeOfDay {
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub nanosecond: u32,
+    /// Seconds east of UTC, or `None` when no zone was written, which YAML reads as UTC.
+    pub offset: Option<i32>,
+}
+
+/// Parses `2001-12-14`, `2001-12-14t21:59:43.10-05:00`, `2001-12-14 21:59:43.10 -5` and the
+/// other spellings the YAML timestamp type allows.
+pub(crate) fn parse_timestamp(s: &str) -> Option<Timestamp> {
+    let mut cursor = Cursor(s);
+    let year = cursor.digits(4, 4)? as i32;
+    cursor.expect('-')?;
+    let month = cursor.digits(1, 2)?;
+    cursor.expect('-')?;
+    let day = cursor.digits(1, 2)?;
+    if cursor.0.is_empty() {
+        return Some(Timestamp {
+            year,
+            month,
+            day,
+            time: None,
+        });
+    }
+    if !cursor.eat('T') && !cursor.eat('t') && !cursor.skip_blanks() {
+        return None;
+    }
+    let mut time = cursor.time_of_day()?;
+    cursor.skip_blanks();
+    time.offset = cursor.offset()?;
+    cursor.0.is_empty().then_some(Timestamp {
+        year,
+        month,
+        day,
+        time: Some(time),
+    })
+}
+
+/// Parses a bare `21:59:43.10` time of day, which carries no zone.
+pub(crate) fn parse_time_of_day(s: &str) -> Option<TimeOfDay> {
+    let mut cursor = Cursor(s);
+    let time = cursor.time_of_day()?;
+    cursor.0.is_empty().then_some(time)
+}
+
+struct Cursor<'a>(&'a str);
+
+impl Cursor<'_> {
+    fn eat(&mut self, c: char) -> bool {
+        match self.0.strip_prefix(c) {
+            Some(rest) => {
+                self.0 = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Option<()> {
+        self.eat(c).then_some(())
+    }
+
+    fn skip_blanks(&mut self) -> bool {
+        let rest = self.0.trim_start_matches([' ', '\t']);
+        let skipped = rest.len() < self.0.len();
+        self.0 = rest;
+        skipped
+    }
+
+    fn digits(&mut self, min: usize, max: usize) -> Option<u32> {
+        let len = self
+            .0
+            .bytes()
+            .take(max)
+            .take_while(u8::is_ascii_digit)
+            .count();
+        if len < min {
+            return None;
+        }
+        let (digits, rest) = self.0.split_at(len);
+        self.0 = rest;
+        digits.parse().ok()
+    }
+
+    fn time_of_day(&mut self) -> Option<TimeOfDay> {
+        let hour = self.digits(1, 2)?;
+        self.expect(':')?;
+        let minute = self.digits(2, 2)?;
+        self.expect(':')?;
+        let second = self.digits(2, 2)?;
+        let mut nanosecond = 0;
+        if self.eat('.') {
+            let len = self.0.bytes().take_while(u8::is_ascii_digit).count();
+            let (fraction, rest) = self.0.split_at(len);
+            self.0 = rest;
+            for (place, digit) in fraction
+                .bytes()
+                .chain(std::iter::repeat(b'0'))
+                .take(9)
+                .enumerate()
+            {
+                nanosecond += u32::from(digit - b'0') * 10u32.pow(8 - place as u32);
+            }
+        }
+        Some(TimeOfDay {
+            hour,
+            minute,
+            second,
+            nanosecond,
+            offset: None,
+        })
+    }
+
+    /// `Z`, `-5`, `+05` or `+05:30`, or nothing at all.
+    fn offset(&mut self) -> Option<Option<i32>> {
+        if self.0.is_empty() {
+            return Some(None);
+        }
+        if self.eat('Z') {
+            return Some(Some(0));
+        }
+        let sign = if self.eat('+') {
+            1
+        } else if self.eat('-') {
+            -1
+        } else {
+            return None;
+        };
+        let hours = self.digits(1, 2)?;
+        let minutes = if self.eat(':') { self.digits(2, 2)? } else { 0 };
+        Some(Some(sign * (hours * 3600 + minutes * 60) as i32))
+    }
+}
@@ -0,0 +1,90 @@
+//! Checks run on values as they are read, registered with
+//! [`Deserializer::add_validator`](crate::de::Deserializer::add_validator).
+//!
+//! Validators are registered against a path such as `spec.replicas` or `containers[*].image`.
+//! Keys are joined with `.`, sequence elements are written `[index]`, and `*` or `[*]` in the
+//! pattern stands for any one key or index.
+
+/// A value about to be deserialized.
+#[derive(Debug)]
+pub struct Node<'a> {
+    /// Where the value sits in the document, e.g. `containers[0].image`.
+    pub path: &'a str,
+    /// The text of the value if it's a scalar, or `None` for sequences and mappings.
+    pub scalar: Option<&'a str>,
+    pub span: saphyr_parser::Span,
+}
+
+pub(crate) type Validator = Box<dyn Fn(&Node<'_>) -> Result<(), String>>;
+
+/// The path to the value being read. Only maintained while validators are registered.
+#[derive(Default)]
+pub(crate) struct Path {
+    text: String,
+    marks: Vec<usize>,
+}
+
+impl Path {
+    pub(crate) fn push_key(&mut self, key: &str) {
+        self.marks.push(self.text.len());
+        if !self.text.is_empty() {
+            self.text.push('.');
+        }
+        self.text.push_str(key);
+    }
+
+    pub(crate) fn push_index(&mut self, index: usize) {
+        use std::fmt::Write;
+
+        self.marks.push(self.text.len());
+        let _ = write!(self.text, "[{index}]");
+    }
+
+    pub(crate) fn pop(&mut self) {
+        if let Some(mark) = self.marks.pop() {
+            self.text.truncate(mark);
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.text.clear();
+        self.marks.clear();
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Splits `a.b[0].c` into `a`, `b`, `[0]`, `c`.
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    let mut rest = path;
+    std::iter::from_fn(move || {
+        rest = rest.strip_prefix('.').unwrap_or(rest);
+        if rest.is_empty() {
+            return None;
+        }
+        let end = if rest.starts_with('[') {
+            rest.find(']').map_or(rest.len(), |close| close + 1)
+        } else {
+            rest.find(['.', '[']).unwrap_or(rest.len())
+        };
+        let (segment, remainder) = rest.split_at(end);
+        rest = remainder;
+        Some(segment)
+    })
+}
+
+pub(crate) fn matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = segments(pattern);
+    let mut path = segments(path);
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, None) => return true,
+            (Some("[*]"), Some(segment)) if segment.starts_with('[') => {}
+            (Some("*"), Some(segment)) if !segment.starts_with('[') => {}
+            (Some(expected), Some(segment)) if expected == segment => {}
+            _ => return false,
+        }
+    }
+}
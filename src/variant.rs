@@ -1,15 +1,55 @@
-use serde::de::{DeserializeSeed, EnumAccess, VariantAccess};
+use serde::de::{
+    DeserializeSeed, EnumAccess, IntoDeserializer, VariantAccess, value::StrDeserializer,
+};
 
-use crate::{de::Deserializer, error::DeserializeError};
+use crate::{
+    de::{Deserializer, nested},
+    error::DeserializeError,
+};
 
 pub(crate) struct Enum<'a, 'de: 'a> {
     de: &'a mut Deserializer<'de>,
+    variants: &'static [&'static str],
+    /// The variant named by a `!Variant` tag, whose node holds the variant's content.
+    tag: Option<String>,
 }
 
 impl<'a, 'de> Enum<'a, 'de> {
-    pub fn new(de: &'a mut Deserializer<'de>) -> Self {
-        Enum { de }
+    pub fn new(de: &'a mut Deserializer<'de>, variants: &'static [&'static str]) -> Self {
+        Enum {
+            de,
+            variants,
+            tag: None,
+        }
     }
+
+    pub fn tagged(
+        de: &'a mut Deserializer<'de>,
+        variants: &'static [&'static str],
+        tag: String,
+    ) -> Self {
+        Enum {
+            de,
+            variants,
+            tag: Some(tag),
+        }
+    }
+}
+
+fn normalize_variant(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Finds the variant `name` refers to, ignoring case and `-`/`_` separators.
+pub(crate) fn match_variant(name: &str, variants: &'static [&'static str]) -> Option<&'static str> {
+    let normalized = normalize_variant(name);
+    variants
+        .iter()
+        .find(|variant| normalize_variant(variant) == normalized)
+        .copied()
 }
 
 impl<'de, 'a> EnumAccess<'de> for Enum<'a, 'de> {
@@ -20,6 +60,26 @@ impl<'de, 'a> EnumAccess<'de> for Enum<'a, 'de> {
     where
         V: DeserializeSeed<'de>,
     {
+        if let Some(tag) = &self.tag {
+            let span = self.de.peek_event()?.map(|(_event, span)| *span);
+            let name = if self.de.options.case_insensitive_enums {
+                match_variant(tag, self.variants).unwrap_or(tag)
+            } else {
+                tag
+            };
+            let deserializer: StrDeserializer<'_, DeserializeError> = name.into_deserializer();
+            let val = seed
+                .deserialize(deserializer)
+                .map_err(|err| err.at_node(span))?;
+            return Ok((val, self));
+        }
+        if self.de.options.case_insensitive_enums {
+            let (name, _span) = self.de.read_scalar_string()?;
+            let name = match_variant(&name, self.variants).unwrap_or(&name);
+            let deserializer: StrDeserializer<'_, DeserializeError> = name.into_deserializer();
+            let val = seed.deserialize(deserializer)?;
+            return Ok((val, self));
+        }
         let val = seed.deserialize(&mut *self.de)?;
         Ok((val, self))
     }
@@ -28,15 +88,23 @@ impl<'de, 'a> EnumAccess<'de> for Enum<'a, 'de> {
 impl<'de, 'a> VariantAccess<'de> for Enum<'a, 'de> {
     type Error = DeserializeError;
 
+    /// A unit variant written as a mapping, `{ Unit: ~ }`, or as a bare `!Unit` tag.
     fn unit_variant(self) -> std::result::Result<(), Self::Error> {
-        unimplemented!("Couldn't figure out to hit this")
+        if self.tag.is_some() {
+            return if self.de.peek_null() {
+                self.de.next_event().map(drop)
+            } else {
+                Err(DeserializeError::TypeError)
+            };
+        }
+        serde::de::Deserialize::deserialize(self.de)
     }
 
-    fn newtype_variant_seed<T>(self, _seed: T) -> std::result::Result<T::Value, Self::Error>
+    fn newtype_variant_seed<T>(self, seed: T) -> std::result::Result<T::Value, Self::Error>
     where
         T: DeserializeSeed<'de>,
     {
-        todo!()
+        nested(|| seed.deserialize(self.de))
     }
 
     fn tuple_variant<V>(self, _len: usize, visitor: V) -> std::result::Result<V::Value, Self::Error>
@@ -0,0 +1,64 @@
+//! Problems in the input that deserializing worked around rather than failing on, collected
+//! through [`Warnings`].
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// Something in the input that was accepted only after being repaired.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Warning {
+    /// Invalid UTF-8, UTF-16 or UTF-32 sequences were replaced with U+FFFD, as allowed by
+    /// [`lossy_utf8`](crate::DeserializerOptions::lossy_utf8).
+    ReplacedInvalidEncoding { replacements: usize },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::ReplacedInvalidEncoding { replacements } => write!(
+                f,
+                "replaced {replacements} invalid sequence(s) in the input with U+FFFD"
+            ),
+        }
+    }
+}
+
+/// Collects the warnings raised while deserializing. Pass a clone to
+/// [`DeserializerOptions::warnings`](crate::DeserializerOptions::warnings) and read them from
+/// the original afterwards.
+///
+/// ```
+/// use saphyr_serde::{DeserializerOptions, warning::{Warning, Warnings}};
+///
+/// let warnings = Warnings::new();
+/// let options = DeserializerOptions::new().lossy_utf8(true).warnings(warnings.clone());
+/// let name: String = saphyr_serde::from_slice_owned_with_options(b"caf\xe9", options).unwrap();
+/// assert_eq!(name, "caf\u{fffd}");
+/// assert_eq!(warnings.take(), [Warning::ReplacedInvalidEncoding { replacements: 1 }]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Warnings(Arc<Mutex<Vec<Warning>>>);
+
+impl Warnings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes and returns the warnings collected so far.
+    pub fn take(&self) -> Vec<Warning> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    pub(crate) fn push(&self, warning: Warning) {
+        self.lock().push(warning);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Warning>> {
+        // A panic while holding the lock can only leave the list short of a warning.
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,142 @@
+//! A `u64` number of bytes written as a human size such as `512KiB`, `10MB` or `1.5 GiB`.
+//!
+//! Units are matched ignoring case. `K`, `KB`, `M`, `MB` and so on are powers of 1000, while
+//! `KiB`, `MiB` and so on are powers of 1024. A bare number is a count of bytes.
+//!
+//! ```
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Limits {
+//!     #[serde(with = "saphyr_serde::with::bytesize")]
+//!     memory: u64,
+//!     #[serde(with = "saphyr_serde::with::bytesize")]
+//!     upload: u64,
+//! }
+//!
+//! let limits: Limits = saphyr_serde::from_str("memory: 512KiB\nupload: 10MB").unwrap();
+//! assert_eq!(limits.memory, 512 * 1024);
+//! assert_eq!(limits.upload, 10_000_000);
+//! ```
+//!
+//! Sizes are written back in the largest binary unit that holds them exactly, so `524288`
+//! becomes `512KiB`.
+
+use std::fmt;
+
+use serde::{
+    Deserializer, Serializer,
+    de::{self, Unexpected, Visitor},
+};
+
+const UNITS: [(&str, u64); 13] = [
+    ("b", 1),
+    ("k", 1000),
+    ("kb", 1000),
+    ("kib", 1 << 10),
+    ("m", 1_000_000),
+    ("mb", 1_000_000),
+    ("mib", 1 << 20),
+    ("g", 1_000_000_000),
+    ("gb", 1_000_000_000),
+    ("gib", 1 << 30),
+    ("t", 1_000_000_000_000),
+    ("tb", 1_000_000_000_000),
+    ("tib", 1 << 40),
+];
+
+const BINARY_UNITS: [(&str, u64); 4] = [
+    ("TiB", 1 << 40),
+    ("GiB", 1 << 30),
+    ("MiB", 1 << 20),
+    ("KiB", 1 << 10),
+];
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(ByteSize)
+}
+
+pub fn serialize<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match BINARY_UNITS
+        .iter()
+        .find(|(_unit, size)| *value != 0 && value.is_multiple_of(*size))
+    {
+        Some((unit, size)) => serializer.collect_str(&format_args!("{}{unit}", value / size)),
+        None => serializer.serialize_u64(*value),
+    }
+}
+
+/// Parses `1.5 GiB` into a number of bytes, rounding any fraction of a byte down.
+fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let number_len = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.' && c != '_')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(number_len);
+    let unit = unit.trim_start();
+    let multiplier = match unit {
+        "" => 1,
+        unit => {
+            UNITS
+                .iter()
+                .find(|(name, _size)| name.eq_ignore_ascii_case(unit))?
+                .1
+        }
+    };
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let whole = whole.replace('_', "");
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let mut bytes = u128::from(whole) * u128::from(multiplier);
+    if !fraction.is_empty() {
+        if !fraction.bytes().all(|b| b.is_ascii_digit()) || fraction.len() > 18 {
+            return None;
+        }
+        let scale = 10u128.pow(fraction.len() as u32);
+        bytes += fraction.parse::<u128>().ok()? * u128::from(multiplier) / scale;
+    }
+    u64::try_from(bytes).ok()
+}
+
+struct ByteSize;
+
+impl<'de> Visitor<'de> for ByteSize {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a size in bytes such as `512KiB` or `10MB`")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<u64, E>
+    where
+        E: de::Error,
+    {
+        Ok(v)
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<u64, E>
+    where
+        E: de::Error,
+    {
+        u64::try_from(v).map_err(|_| E::invalid_value(Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<u64, E>
+    where
+        E: de::Error,
+    {
+        parse_size(v).ok_or_else(|| E::invalid_value(Unexpected::Str(v), &self))
+    }
+}
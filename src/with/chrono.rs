@@ -0,0 +1,160 @@
+//! `chrono` types read from YAML timestamps, which include forms such as
+//! `2001-12-14 21:59:43.10 -5` that chrono's own RFC 3339 parsing rejects.
+//!
+//! ```
+//! use chrono::{DateTime, NaiveDate, Utc};
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Release {
+//!     #[serde(with = "saphyr_serde::with::chrono::datetime_utc")]
+//!     published: DateTime<Utc>,
+//!     #[serde(with = "saphyr_serde::with::chrono::naive_date")]
+//!     frozen: NaiveDate,
+//! }
+//!
+//! let release: Release =
+//!     saphyr_serde::de::from_str("published: 2001-12-14 21:59:43.10 -5\nfrozen: 2001-12-01")
+//!         .unwrap();
+//! assert_eq!(release.frozen, NaiveDate::from_ymd_opt(2001, 12, 1).unwrap());
+//! ```
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
+
+use super::parse_str;
+use crate::timestamp::{Timestamp, parse_time_of_day, parse_timestamp};
+
+fn naive_datetime(timestamp: &Timestamp) -> Option<NaiveDateTime> {
+    let date = NaiveDate::from_ymd_opt(timestamp.year, timestamp.month, timestamp.day)?;
+    match &timestamp.time {
+        Some(time) => date.and_hms_nano_opt(time.hour, time.minute, time.second, time.nanosecond),
+        None => date.and_hms_opt(0, 0, 0),
+    }
+}
+
+fn datetime(timestamp: &Timestamp) -> Option<DateTime<FixedOffset>> {
+    let offset = timestamp.time.as_ref().and_then(|time| time.offset);
+    FixedOffset::east_opt(offset.unwrap_or(0))?
+        .from_local_datetime(&naive_datetime(timestamp)?)
+        .single()
+}
+
+/// A `DateTime<Utc>`, where a timestamp without a zone is taken to be UTC.
+pub mod datetime_utc {
+    use chrono::{DateTime, SecondsFormat, Utc};
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::parse_str(deserializer, "a YAML timestamp", |text| {
+            let timestamp = super::parse_timestamp(text)?;
+            Some(super::datetime(&timestamp)?.with_timezone(&Utc))
+        })
+    }
+
+    pub fn serialize<S>(value: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&value.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+}
+
+/// A `DateTime<FixedOffset>` that keeps the zone written in the document.
+pub mod datetime_fixed_offset {
+    use chrono::{DateTime, FixedOffset, SecondsFormat};
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<FixedOffset>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::parse_str(deserializer, "a YAML timestamp", |text| {
+            super::datetime(&super::parse_timestamp(text)?)
+        })
+    }
+
+    pub fn serialize<S>(value: &DateTime<FixedOffset>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&value.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+}
+
+/// A `NaiveDateTime`, rejecting timestamps that name a zone.
+pub mod naive_datetime {
+    use chrono::NaiveDateTime;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::parse_str(deserializer, "a YAML timestamp without a zone", |text| {
+            let timestamp = super::parse_timestamp(text)?;
+            match &timestamp.time {
+                Some(time) if time.offset.is_some() => None,
+                _ => super::naive_datetime(&timestamp),
+            }
+        })
+    }
+
+    pub fn serialize<S>(value: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(value)
+    }
+}
+
+/// A `NaiveDate` written as `2001-12-14`.
+pub mod naive_date {
+    use chrono::NaiveDate;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::parse_str(deserializer, "a YAML date", |text| {
+            let timestamp = super::parse_timestamp(text)?;
+            if timestamp.time.is_some() {
+                return None;
+            }
+            NaiveDate::from_ymd_opt(timestamp.year, timestamp.month, timestamp.day)
+        })
+    }
+
+    pub fn serialize<S>(value: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(value)
+    }
+}
+
+/// A `NaiveTime` written as `21:59:43.10`.
+pub mod naive_time {
+    use chrono::NaiveTime;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::parse_str(deserializer, "a time of day", |text| {
+            let time = super::parse_time_of_day(text)?;
+            NaiveTime::from_hms_nano_opt(time.hour, time.minute, time.second, time.nanosecond)
+        })
+    }
+
+    pub fn serialize<S>(value: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(value)
+    }
+}
@@ -0,0 +1,456 @@
+//! A buffered copy of what a value serializes to, for adapters that can only hand a value on once
+//! all of it has been seen, such as the fields of a variant [`singleton_map`](super::singleton_map)
+//! writes as a map.
+
+use std::marker::PhantomData;
+
+use serde::{
+    Serialize, Serializer,
+    ser::{
+        self, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+        SerializeTupleStruct, SerializeTupleVariant,
+    },
+};
+
+/// The fields of a struct or struct variant, in the order they were written.
+pub(super) type Fields = Vec<(&'static str, Content)>;
+
+pub(super) enum Content {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Content>),
+    Unit,
+    UnitStruct(&'static str),
+    UnitVariant(&'static str, u32, &'static str),
+    NewtypeStruct(&'static str, Box<Content>),
+    NewtypeVariant(&'static str, u32, &'static str, Box<Content>),
+    Seq(Vec<Content>),
+    Tuple(Vec<Content>),
+    TupleStruct(&'static str, Vec<Content>),
+    TupleVariant(&'static str, u32, &'static str, Vec<Content>),
+    Map(Vec<(Content, Content)>),
+    Struct(&'static str, Fields),
+    StructVariant(&'static str, u32, &'static str, Fields),
+}
+
+impl Content {
+    /// Buffers what `value` serializes to, failing with `E` where the value fails to serialize.
+    pub(super) fn of<T, E>(value: &T) -> Result<Content, E>
+    where
+        T: Serialize + ?Sized,
+        E: ser::Error,
+    {
+        value.serialize(ContentSerializer(PhantomData))
+    }
+}
+
+impl Serialize for Content {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Content::Bool(v) => serializer.serialize_bool(*v),
+            Content::I8(v) => serializer.serialize_i8(*v),
+            Content::I16(v) => serializer.serialize_i16(*v),
+            Content::I32(v) => serializer.serialize_i32(*v),
+            Content::I64(v) => serializer.serialize_i64(*v),
+            Content::I128(v) => serializer.serialize_i128(*v),
+            Content::U8(v) => serializer.serialize_u8(*v),
+            Content::U16(v) => serializer.serialize_u16(*v),
+            Content::U32(v) => serializer.serialize_u32(*v),
+            Content::U64(v) => serializer.serialize_u64(*v),
+            Content::U128(v) => serializer.serialize_u128(*v),
+            Content::F32(v) => serializer.serialize_f32(*v),
+            Content::F64(v) => serializer.serialize_f64(*v),
+            Content::Char(v) => serializer.serialize_char(*v),
+            Content::String(v) => serializer.serialize_str(v),
+            Content::Bytes(v) => serializer.serialize_bytes(v),
+            Content::None => serializer.serialize_none(),
+            Content::Some(content) => serializer.serialize_some(content),
+            Content::Unit => serializer.serialize_unit(),
+            Content::UnitStruct(name) => serializer.serialize_unit_struct(name),
+            Content::UnitVariant(name, index, variant) => {
+                serializer.serialize_unit_variant(name, *index, variant)
+            }
+            Content::NewtypeStruct(name, content) => {
+                serializer.serialize_newtype_struct(name, content)
+            }
+            Content::NewtypeVariant(name, index, variant, content) => {
+                serializer.serialize_newtype_variant(name, *index, variant, content)
+            }
+            Content::Seq(elements) => {
+                let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                for element in elements {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            Content::Tuple(elements) => {
+                let mut tuple = serializer.serialize_tuple(elements.len())?;
+                for element in elements {
+                    tuple.serialize_element(element)?;
+                }
+                tuple.end()
+            }
+            Content::TupleStruct(name, fields) => {
+                let mut tuple = serializer.serialize_tuple_struct(name, fields.len())?;
+                for field in fields {
+                    tuple.serialize_field(field)?;
+                }
+                tuple.end()
+            }
+            Content::TupleVariant(name, index, variant, fields) => {
+                let mut tuple =
+                    serializer.serialize_tuple_variant(name, *index, variant, fields.len())?;
+                for field in fields {
+                    tuple.serialize_field(field)?;
+                }
+                tuple.end()
+            }
+            Content::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Content::Struct(name, fields) => {
+                let mut map = serializer.serialize_struct(name, fields.len())?;
+                for (key, value) in fields {
+                    map.serialize_field(key, value)?;
+                }
+                map.end()
+            }
+            Content::StructVariant(name, index, variant, fields) => {
+                let mut map =
+                    serializer.serialize_struct_variant(name, *index, variant, fields.len())?;
+                for (key, value) in fields {
+                    map.serialize_field(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// Serializes into [`Content`], with errors of the type the buffered value is eventually
+/// serialized with.
+struct ContentSerializer<E>(PhantomData<E>);
+
+macro_rules! serialize_scalars {
+    ($($method:ident($ty:ty) => $variant:ident,)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Content, E> {
+                Ok(Content::$variant(v.into()))
+            }
+        )*
+    };
+}
+
+impl<E> Serializer for ContentSerializer<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+    type SerializeSeq = SerializeElements<E>;
+    type SerializeTuple = SerializeElements<E>;
+    type SerializeTupleStruct = SerializeElements<E>;
+    type SerializeTupleVariant = SerializeElements<E>;
+    type SerializeMap = SerializeEntries<E>;
+    type SerializeStruct = SerializeFields<E>;
+    type SerializeStructVariant = SerializeFields<E>;
+
+    serialize_scalars! {
+        serialize_bool(bool) => Bool,
+        serialize_i8(i8) => I8,
+        serialize_i16(i16) => I16,
+        serialize_i32(i32) => I32,
+        serialize_i64(i64) => I64,
+        serialize_i128(i128) => I128,
+        serialize_u8(u8) => U8,
+        serialize_u16(u16) => U16,
+        serialize_u32(u32) => U32,
+        serialize_u64(u64) => U64,
+        serialize_u128(u128) => U128,
+        serialize_f32(f32) => F32,
+        serialize_f64(f64) => F64,
+        serialize_char(char) => Char,
+        serialize_str(&str) => String,
+        serialize_bytes(&[u8]) => Bytes,
+    }
+
+    fn serialize_none(self) -> Result<Content, E> {
+        Ok(Content::None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Content, E>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(Content::Some(Box::new(Content::of(value)?)))
+    }
+
+    fn serialize_unit(self) -> Result<Content, E> {
+        Ok(Content::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Content, E> {
+        Ok(Content::UnitStruct(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<Content, E> {
+        Ok(Content::UnitVariant(name, index, variant))
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Content, E>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(Content::NewtypeStruct(name, Box::new(Content::of(value)?)))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Content, E>
+    where
+        T: Serialize + ?Sized,
+    {
+        let content = Box::new(Content::of(value)?);
+        Ok(Content::NewtypeVariant(name, index, variant, content))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeElements<E>, E> {
+        Ok(SerializeElements::new(len.unwrap_or(0), Content::Seq))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeElements<E>, E> {
+        Ok(SerializeElements::new(len, Content::Tuple))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeElements<E>, E> {
+        Ok(SerializeElements::new(len, move |fields| {
+            Content::TupleStruct(name, fields)
+        }))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeElements<E>, E> {
+        Ok(SerializeElements::new(len, move |fields| {
+            Content::TupleVariant(name, index, variant, fields)
+        }))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeEntries<E>, E> {
+        Ok(SerializeEntries {
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+            error: PhantomData,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<SerializeFields<E>, E> {
+        Ok(SerializeFields::new(len, move |fields| {
+            Content::Struct(name, fields)
+        }))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeFields<E>, E> {
+        Ok(SerializeFields::new(len, move |fields| {
+            Content::StructVariant(name, index, variant, fields)
+        }))
+    }
+}
+
+/// The elements of a sequence, tuple, tuple struct or tuple variant, and how to wrap them up.
+struct SerializeElements<E> {
+    elements: Vec<Content>,
+    wrap: Box<dyn FnOnce(Vec<Content>) -> Content>,
+    error: PhantomData<E>,
+}
+
+impl<E> SerializeElements<E>
+where
+    E: ser::Error,
+{
+    fn new(len: usize, wrap: impl FnOnce(Vec<Content>) -> Content + 'static) -> Self {
+        Self {
+            elements: Vec::with_capacity(len),
+            wrap: Box::new(wrap),
+            error: PhantomData,
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.elements.push(Content::of(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Content, E> {
+        Ok((self.wrap)(self.elements))
+    }
+}
+
+macro_rules! serialize_elements {
+    ($($trait:ident::$method:ident,)*) => {
+        $(
+            impl<E> $trait for SerializeElements<E>
+            where
+                E: ser::Error,
+            {
+                type Ok = Content;
+                type Error = E;
+
+                fn $method<T>(&mut self, value: &T) -> Result<(), E>
+                where
+                    T: Serialize + ?Sized,
+                {
+                    self.push(value)
+                }
+
+                fn end(self) -> Result<Content, E> {
+                    self.finish()
+                }
+            }
+        )*
+    };
+}
+
+serialize_elements! {
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field,
+}
+
+struct SerializeEntries<E> {
+    entries: Vec<(Content, Content)>,
+    key: Option<Content>,
+    error: PhantomData<E>,
+}
+
+impl<E> SerializeMap for SerializeEntries<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), E>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.key = Some(Content::of(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| E::custom("serialize_value called before serialize_key"))?;
+        self.entries.push((key, Content::of(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content, E> {
+        Ok(Content::Map(self.entries))
+    }
+}
+
+/// The fields of a struct or struct variant, and how to wrap them up.
+struct SerializeFields<E> {
+    fields: Fields,
+    wrap: Box<dyn FnOnce(Fields) -> Content>,
+    error: PhantomData<E>,
+}
+
+impl<E> SerializeFields<E>
+where
+    E: ser::Error,
+{
+    fn new(len: usize, wrap: impl FnOnce(Fields) -> Content + 'static) -> Self {
+        Self {
+            fields: Vec::with_capacity(len),
+            wrap: Box::new(wrap),
+            error: PhantomData,
+        }
+    }
+}
+
+macro_rules! serialize_fields {
+    ($($trait:ident,)*) => {
+        $(
+            impl<E> $trait for SerializeFields<E>
+            where
+                E: ser::Error,
+            {
+                type Ok = Content;
+                type Error = E;
+
+                fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), E>
+                where
+                    T: Serialize + ?Sized,
+                {
+                    self.fields.push((key, Content::of(value)?));
+                    Ok(())
+                }
+
+                fn end(self) -> Result<Content, E> {
+                    Ok((self.wrap)(self.fields))
+                }
+            }
+        )*
+    };
+}
+
+serialize_fields! {
+    SerializeStruct,
+    SerializeStructVariant,
+}
@@ -0,0 +1,43 @@
+//! An `Option<Option<T>>` field that tells a missing key, `None`, from an explicit null,
+//! `Some(None)`. Plain `Option<T>` fields read both as `None`.
+//!
+//! ```
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Patch {
+//!     #[serde(default, with = "saphyr_serde::with::double_option")]
+//!     description: Option<Option<String>>,
+//! }
+//!
+//! let keep: Patch = saphyr_serde::from_str("{}").unwrap();
+//! let clear: Patch = saphyr_serde::from_str("description: null").unwrap();
+//! let set: Patch = saphyr_serde::from_str("description: text").unwrap();
+//! assert_eq!(keep.description, None);
+//! assert_eq!(clear.description, Some(None));
+//! assert_eq!(set.description, Some(Some(String::from("text"))));
+//! ```
+//!
+//! The `default` attribute is what makes a missing key `None`; add
+//! `skip_serializing_if = "Option::is_none"` to leave it out again when writing.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
+}
+
+pub fn serialize<T, S>(value: &Option<Option<T>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    match value {
+        Some(inner) => inner.serialize(serializer),
+        None => serializer.serialize_none(),
+    }
+}
@@ -0,0 +1,217 @@
+//! An externally tagged enum that keeps variants it doesn't know in a fallback instead of
+//! failing, so documents written for a newer version of a schema can still be read.
+//!
+//! The enum implements [`Fallback`] to say how to build the fallback from the variant's name and
+//! body. Mark the fallback variant `#[serde(skip_deserializing)]` so it can't be selected by
+//! name itself. A variant written as a bare scalar has no body, so it gets `Body::default()`.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use saphyr_serde::with::enum_fallback::Fallback;
+//!
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! enum Step {
+//!     Build { target: String },
+//!     Test,
+//!     #[serde(skip_deserializing)]
+//!     Other(String, serde_json::Value),
+//! }
+//!
+//! impl Fallback for Step {
+//!     type Body = serde_json::Value;
+//!
+//!     fn fallback(variant: String, body: serde_json::Value) -> Self {
+//!         Step::Other(variant, body)
+//!     }
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Pipeline {
+//!     #[serde(with = "saphyr_serde::with::enum_fallback")]
+//!     first: Step,
+//!     #[serde(with = "saphyr_serde::with::enum_fallback")]
+//!     second: Step,
+//! }
+//!
+//! let pipeline: Pipeline =
+//!     saphyr_serde::from_str("first: Test\nsecond: {Deploy: {region: eu}}").unwrap();
+//! assert_eq!(pipeline.first, Step::Test);
+//! assert_eq!(
+//!     pipeline.second,
+//!     Step::Other("Deploy".into(), serde_json::json!({"region": "eu"}))
+//! );
+//! ```
+
+use std::{cell::Cell, fmt, marker::PhantomData};
+
+use serde::{
+    Deserialize, Deserializer,
+    de::{DeserializeSeed, EnumAccess, Error, IntoDeserializer, VariantAccess, Visitor},
+    forward_to_deserialize_any,
+};
+
+/// How to keep a variant the enum doesn't declare.
+pub trait Fallback: Sized {
+    /// What the unknown variant's content is read as, e.g. `serde_json::Value` to keep all of
+    /// it, or `IgnoredAny` to drop it.
+    type Body;
+
+    fn fallback(variant: String, body: Self::Body) -> Self;
+}
+
+pub fn deserialize<'de, D, E>(deserializer: D) -> Result<E, D::Error>
+where
+    D: Deserializer<'de>,
+    E: Deserialize<'de> + Fallback,
+    E::Body: Deserialize<'de> + Default,
+{
+    let (name, variants) = declared_variants::<E>();
+    deserializer.deserialize_enum(
+        name,
+        variants,
+        FallbackVisitor {
+            variants,
+            marker: PhantomData,
+        },
+    )
+}
+
+/// The name and variants the enum's `Deserialize` impl asks for, found by running it against a
+/// deserializer that only records them.
+fn declared_variants<'de, E>() -> (&'static str, &'static [&'static str])
+where
+    E: Deserialize<'de>,
+{
+    let declared = Cell::new(("", &[][..]));
+    let _ = E::deserialize(Probe(&declared));
+    declared.get()
+}
+
+type Declared = (&'static str, &'static [&'static str]);
+
+struct Probe<'a>(&'a Cell<Declared>);
+
+impl<'de> Deserializer<'de> for Probe<'_> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Self::Error::custom("not an externally tagged enum"))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0.set((name, variants));
+        Err(Self::Error::custom("only probing for variants"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct FallbackVisitor<E> {
+    variants: &'static [&'static str],
+    marker: PhantomData<E>,
+}
+
+impl<'de, E> Visitor<'de> for FallbackVisitor<E>
+where
+    E: Deserialize<'de> + Fallback,
+    E::Body: Deserialize<'de> + Default,
+{
+    type Value = E;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an enum variant")
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<E, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (variant, content) = data.variant::<String>()?;
+        if self.variants.contains(&variant.as_str()) {
+            return E::deserialize(Known { variant, content });
+        }
+        let read = Cell::new(false);
+        let body = match content.newtype_variant_seed(BodySeed(&read, PhantomData)) {
+            Ok(body) => body,
+            // A bare variant name never reaches the seed.
+            Err(_) if !read.get() => E::Body::default(),
+            Err(err) => return Err(err),
+        };
+        Ok(E::fallback(variant, body))
+    }
+}
+
+/// Gives the enum's `Deserialize` impl the variant that was already read.
+struct Known<A> {
+    variant: String,
+    content: A,
+}
+
+impl<'de, A> Deserializer<'de> for Known<A>
+where
+    A: VariantAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de, A> EnumAccess<'de> for Known<A>
+where
+    A: VariantAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = A;
+
+    fn variant_seed<S>(self, seed: S) -> Result<(S::Value, A), A::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self.content))
+    }
+}
+
+/// Reads the body of an unknown variant, noting whether there was one to read.
+struct BodySeed<'r, T>(&'r Cell<bool>, PhantomData<T>);
+
+impl<'de, T> DeserializeSeed<'de> for BodySeed<'_, T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = T;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.set(true);
+        T::deserialize(deserializer)
+    }
+}
@@ -0,0 +1,37 @@
+//! An integer written in hexadecimal, `0x1F`, with a sign rather than in two's complement when
+//! negative, `-0x1`. Any radix is accepted when reading, so existing `31` values keep working.
+//!
+//! ```
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Register {
+//!     #[serde(with = "saphyr_serde::with::hex_int")]
+//!     flags: u32,
+//! }
+//!
+//! let register: Register = saphyr_serde::from_str("flags: 0x1F").unwrap();
+//! assert_eq!(register.flags, 31);
+//! ```
+
+use serde::{Deserializer, Serializer};
+
+use super::Integer;
+
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<i128>,
+{
+    super::deserialize_int(deserializer)
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Integer,
+    S: Serializer,
+{
+    let (negative, magnitude) = value.sign_magnitude();
+    let sign = if negative { "-" } else { "" };
+    serializer.collect_str(&format_args!("{sign}0x{magnitude:X}"))
+}
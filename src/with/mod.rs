@@ -0,0 +1,114 @@
+//! Adapters for use with `#[serde(with = "...")]`, for types whose own `Deserialize` impl
+//! doesn't understand the way YAML writes them.
+
+pub mod bytesize;
+#[cfg(feature = "chrono")]
+pub mod chrono;
+mod content;
+pub mod double_option;
+pub mod enum_fallback;
+pub mod hex_int;
+pub mod octal_int;
+pub mod singleton_map;
+pub mod string_or_struct;
+#[cfg(feature = "time")]
+pub mod time;
+
+/// Reads a scalar and converts it, reporting `expected` if `convert` rejects the text.
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn parse_str<'de, D, T>(
+    deserializer: D,
+    expected: &'static str,
+    convert: impl FnOnce(&str) -> Option<T>,
+) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::{
+        Deserialize,
+        de::{Error, Unexpected},
+    };
+
+    let text = String::deserialize(deserializer)?;
+    convert(&text).ok_or_else(|| D::Error::invalid_value(Unexpected::Str(&text), &expected))
+}
+
+/// The primitive integer types, which [`hex_int`] and [`octal_int`] write as a sign and a
+/// magnitude.
+pub trait Integer: Copy + sealed::Sealed {
+    /// Whether the value is negative, and its absolute value.
+    fn sign_magnitude(self) -> (bool, u128);
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_integer {
+    (signed: $($signed:ty)*; unsigned: $($unsigned:ty)*) => {
+        $(
+            impl sealed::Sealed for $signed {}
+            impl Integer for $signed {
+                fn sign_magnitude(self) -> (bool, u128) {
+                    (self < 0, self.unsigned_abs() as u128)
+                }
+            }
+        )*
+        $(
+            impl sealed::Sealed for $unsigned {}
+            impl Integer for $unsigned {
+                fn sign_magnitude(self) -> (bool, u128) {
+                    (false, self as u128)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(signed: i8 i16 i32 i64 i128 isize; unsigned: u8 u16 u32 u64 u128 usize);
+
+/// Reads an integer written in any radix YAML allows, `31`, `0x1F`, `0o37` or `0b11111`,
+/// whether as a number or a string.
+fn deserialize_int<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: TryFrom<i128>,
+{
+    deserializer.deserialize_any(RadixInt(std::marker::PhantomData))
+}
+
+struct RadixInt<T>(std::marker::PhantomData<T>);
+
+impl<'de, T> serde::de::Visitor<'de> for RadixInt<T>
+where
+    T: TryFrom<i128>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("an integer such as `31`, `0x1F` or `0o37`")
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<T, E>
+    where
+        E: serde::de::Error,
+    {
+        T::try_from(v.into()).map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<T, E>
+    where
+        E: serde::de::Error,
+    {
+        T::try_from(v.into())
+            .map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<T, E>
+    where
+        E: serde::de::Error,
+    {
+        crate::scalar::parse_int(v, crate::options::Schema::Yaml12)
+            .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+    }
+}
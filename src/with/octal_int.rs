@@ -0,0 +1,37 @@
+//! An integer written in octal, `0o755`, with a sign rather than in two's complement when
+//! negative, `-0o1`. Any radix is accepted when reading, so existing `493` values keep working.
+//!
+//! ```
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct File {
+//!     #[serde(with = "saphyr_serde::with::octal_int")]
+//!     mode: u32,
+//! }
+//!
+//! let file: File = saphyr_serde::from_str("mode: 0o755").unwrap();
+//! assert_eq!(file.mode, 493);
+//! ```
+
+use serde::{Deserializer, Serializer};
+
+use super::Integer;
+
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<i128>,
+{
+    super::deserialize_int(deserializer)
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Integer,
+    S: Serializer,
+{
+    let (negative, magnitude) = value.sign_magnitude();
+    let sign = if negative { "-" } else { "" };
+    serializer.collect_str(&format_args!("{sign}0o{magnitude:o}"))
+}
@@ -0,0 +1,560 @@
+//! Enums written as a map with a single entry, `Variant: value`, with unit variants written as a
+//! bare `Variant`. This is the form [`Deserializer`](crate::de::Deserializer) reads enums in
+//! anyway, so the adapter is for code moving over from `serde_yaml::with::singleton_map` and
+//! for formats that represent enums some other way.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, PartialEq, Debug)]
+//! enum Shape {
+//!     Circle { radius: f64 },
+//!     Square(f64),
+//! }
+//!
+//! #[derive(Serialize, Deserialize, PartialEq, Debug)]
+//! struct Drawing {
+//!     #[serde(with = "saphyr_serde::with::singleton_map")]
+//!     shape: Shape,
+//! }
+//!
+//! let drawing: Drawing = saphyr_serde::from_str("shape:\n  Circle:\n    radius: 1.5").unwrap();
+//! assert_eq!(drawing.shape, Shape::Circle { radius: 1.5 });
+//! assert_eq!(
+//!     serde_json::to_string(&drawing).unwrap(),
+//!     r#"{"shape":{"Circle":{"radius":1.5}}}"#
+//! );
+//! ```
+
+use std::fmt;
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, VariantAccess, Visitor},
+    ser::{self, SerializeMap},
+};
+
+use super::content::{Content, Fields};
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + ?Sized,
+    S: Serializer,
+{
+    value.serialize(SingletonMapSerializer {
+        delegate: serializer,
+    })
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(SingletonMapDeserializer {
+        delegate: deserializer,
+    })
+}
+
+/// Forwards the `Serializer` methods that don't involve enums.
+macro_rules! forward_serialize {
+    ($($method:ident($($arg:ident: $ty:ty),*) -> $ok:ty;)*) => {
+        $(
+            fn $method(self, $($arg: $ty),*) -> Result<$ok, S::Error> {
+                self.delegate.$method($($arg),*)
+            }
+        )*
+    };
+}
+
+/// Writes newtype, tuple and struct variants as a map with a single entry, passing everything
+/// else straight through to `delegate`.
+struct SingletonMapSerializer<S> {
+    delegate: S,
+}
+
+impl<S> Serializer for SingletonMapSerializer<S>
+where
+    S: Serializer,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = S::SerializeSeq;
+    type SerializeTuple = S::SerializeTuple;
+    type SerializeTupleStruct = S::SerializeTupleStruct;
+    type SerializeTupleVariant = TupleVariantAsMap<S::SerializeMap>;
+    type SerializeMap = S::SerializeMap;
+    type SerializeStruct = S::SerializeStruct;
+    type SerializeStructVariant = StructVariantAsMap<S::SerializeMap>;
+
+    forward_serialize! {
+        serialize_bool(v: bool) -> S::Ok;
+        serialize_i8(v: i8) -> S::Ok;
+        serialize_i16(v: i16) -> S::Ok;
+        serialize_i32(v: i32) -> S::Ok;
+        serialize_i64(v: i64) -> S::Ok;
+        serialize_i128(v: i128) -> S::Ok;
+        serialize_u8(v: u8) -> S::Ok;
+        serialize_u16(v: u16) -> S::Ok;
+        serialize_u32(v: u32) -> S::Ok;
+        serialize_u64(v: u64) -> S::Ok;
+        serialize_u128(v: u128) -> S::Ok;
+        serialize_f32(v: f32) -> S::Ok;
+        serialize_f64(v: f64) -> S::Ok;
+        serialize_char(v: char) -> S::Ok;
+        serialize_str(v: &str) -> S::Ok;
+        serialize_bytes(v: &[u8]) -> S::Ok;
+        serialize_none() -> S::Ok;
+        serialize_unit() -> S::Ok;
+        serialize_unit_struct(name: &'static str) -> S::Ok;
+        serialize_unit_variant(name: &'static str, index: u32, variant: &'static str) -> S::Ok;
+        serialize_seq(len: Option<usize>) -> S::SerializeSeq;
+        serialize_tuple(len: usize) -> S::SerializeTuple;
+        serialize_tuple_struct(name: &'static str, len: usize) -> S::SerializeTupleStruct;
+        serialize_map(len: Option<usize>) -> S::SerializeMap;
+        serialize_struct(name: &'static str, len: usize) -> S::SerializeStruct;
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.delegate.serialize_some(&SingletonMapped(value))
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.delegate.serialize_newtype_struct(name, value)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut map = self.delegate.serialize_map(Some(1))?;
+        map.serialize_entry(variant, value)?;
+        map.end()
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        let mut map = self.delegate.serialize_map(Some(1))?;
+        map.serialize_key(variant)?;
+        Ok(TupleVariantAsMap {
+            map,
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        let mut map = self.delegate.serialize_map(Some(1))?;
+        map.serialize_key(variant)?;
+        Ok(StructVariantAsMap {
+            map,
+            variant,
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.delegate.is_human_readable()
+    }
+}
+
+/// Applies the adapter to the contents of an `Option`.
+struct SingletonMapped<'a, T: ?Sized>(&'a T);
+
+impl<T> Serialize for SingletonMapped<'_, T>
+where
+    T: Serialize + ?Sized,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(self.0, serializer)
+    }
+}
+
+/// The map a tuple variant is written as. Its key is written straight away, but the fields
+/// arrive one at a time and can only be handed to the map as a single value, so they are
+/// buffered until the variant ends.
+struct TupleVariantAsMap<M> {
+    map: M,
+    fields: Vec<Content>,
+}
+
+impl<M> ser::SerializeTupleVariant for TupleVariantAsMap<M>
+where
+    M: SerializeMap,
+{
+    type Ok = M::Ok;
+    type Error = M::Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), M::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.fields.push(Content::of(value)?);
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<M::Ok, M::Error> {
+        self.map.serialize_value(&Content::Tuple(self.fields))?;
+        self.map.end()
+    }
+}
+
+/// Like [`TupleVariantAsMap`], for a struct variant.
+struct StructVariantAsMap<M> {
+    map: M,
+    variant: &'static str,
+    fields: Fields,
+}
+
+impl<M> ser::SerializeStructVariant for StructVariantAsMap<M>
+where
+    M: SerializeMap,
+{
+    type Ok = M::Ok;
+    type Error = M::Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), M::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.fields.push((key, Content::of(value)?));
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<M::Ok, M::Error> {
+        self.map
+            .serialize_value(&Content::Struct(self.variant, self.fields))?;
+        self.map.end()
+    }
+}
+
+/// Forwards every `deserialize_*` method except `deserialize_enum` and `deserialize_option`.
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.delegate.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+struct SingletonMapDeserializer<D> {
+    delegate: D,
+}
+
+impl<'de, D> Deserializer<'de> for SingletonMapDeserializer<D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.delegate
+            .deserialize_option(OptionVisitor { delegate: visitor })
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // Asking for an enum rather than any value keeps variants named `true`, `null` or `1`
+        // from being resolved into booleans, nulls and numbers first.
+        self.delegate
+            .deserialize_enum(name, variants, SingletonMapAsEnum { name, visitor })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.delegate.is_human_readable()
+    }
+}
+
+/// Applies the adapter to the contents of an `Option`.
+struct OptionVisitor<V> {
+    delegate: V,
+}
+
+impl<'de, V> Visitor<'de> for OptionVisitor<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.delegate.expecting(f)
+    }
+
+    fn visit_none<E>(self) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.delegate.visit_none()
+    }
+
+    fn visit_unit<E>(self) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.delegate.visit_unit()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.delegate.visit_some(SingletonMapDeserializer {
+            delegate: deserializer,
+        })
+    }
+}
+
+/// Passes on an enum the format reads itself, and otherwise reads a bare string as a unit variant
+/// or a single entry map as any variant.
+struct SingletonMapAsEnum<V> {
+    name: &'static str,
+    visitor: V,
+}
+
+impl<'de, V> Visitor<'de> for SingletonMapAsEnum<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "enum {} as a variant name or a map with one entry",
+            self.name
+        )
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_enum(v.into_deserializer())
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor
+            .visit_enum(de::value::BorrowedStrDeserializer::new(v))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_enum(v.into_deserializer())
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<V::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.visitor.visit_enum(SingletonMapAccess {
+            name: self.name,
+            map,
+        })
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<V::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        self.visitor.visit_enum(data)
+    }
+}
+
+struct SingletonMapAccess<A> {
+    name: &'static str,
+    map: A,
+}
+
+impl<'de, A> SingletonMapAccess<A>
+where
+    A: MapAccess<'de>,
+{
+    fn end(mut self) -> Result<(), A::Error> {
+        match self.map.next_key::<de::IgnoredAny>()? {
+            None => Ok(()),
+            Some(_) => Err(de::Error::invalid_length(2, &"a map with one entry")),
+        }
+    }
+}
+
+impl<'de, A> EnumAccess<'de> for SingletonMapAccess<A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = Self;
+
+    fn variant_seed<S>(mut self, seed: S) -> Result<(S::Value, Self), A::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        match self.map.next_key_seed(seed)? {
+            Some(variant) => Ok((variant, self)),
+            None => Err(de::Error::invalid_length(0, &"a map with one entry")),
+        }
+    }
+}
+
+impl<'de, A> VariantAccess<'de> for SingletonMapAccess<A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn unit_variant(mut self) -> Result<(), A::Error> {
+        self.map.next_value::<()>()?;
+        self.end()
+    }
+
+    fn newtype_variant_seed<S>(mut self, seed: S) -> Result<S::Value, A::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let value = self.map.next_value_seed(seed)?;
+        self.end()?;
+        Ok(value)
+    }
+
+    fn tuple_variant<V>(mut self, len: usize, visitor: V) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let value = self.map.next_value_seed(TupleSeed { len, visitor })?;
+        self.end()?;
+        Ok(value)
+    }
+
+    fn struct_variant<V>(
+        mut self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let name = self.name;
+        let value = self.map.next_value_seed(StructSeed {
+            name,
+            fields,
+            visitor,
+        })?;
+        self.end()?;
+        Ok(value)
+    }
+}
+
+struct TupleSeed<V> {
+    len: usize,
+    visitor: V,
+}
+
+impl<'de, V> DeserializeSeed<'de> for TupleSeed<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(self.len, self.visitor)
+    }
+}
+
+struct StructSeed<V> {
+    name: &'static str,
+    fields: &'static [&'static str],
+    visitor: V,
+}
+
+impl<'de, V> DeserializeSeed<'de> for StructSeed<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(self.name, self.fields, self.visitor)
+    }
+}
@@ -0,0 +1,116 @@
+//! A value written either as a scalar shorthand, parsed with `FromStr`, or as the full mapping.
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize, PartialEq, Debug)]
+//! struct Redis {
+//!     host: String,
+//!     port: u16,
+//! }
+//!
+//! impl FromStr for Redis {
+//!     type Err = String;
+//!
+//!     fn from_str(s: &str) -> Result<Self, String> {
+//!         let (host, port) = s.split_once(':').ok_or("expected host:port")?;
+//!         let port = port.parse().map_err(|_| "invalid port")?;
+//!         Ok(Redis { host: host.to_string(), port })
+//!     }
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Config {
+//!     #[serde(with = "saphyr_serde::with::string_or_struct")]
+//!     redis: Redis,
+//! }
+//!
+//! let short: Config = saphyr_serde::from_str("redis: localhost:6379").unwrap();
+//! let long: Config = saphyr_serde::from_str("redis: {host: localhost, port: 6379}").unwrap();
+//! assert_eq!(short.redis, long.redis);
+//! ```
+
+use std::{fmt, marker::PhantomData, str::FromStr};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, MapAccess, Visitor, value::MapAccessDeserializer},
+};
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de> + FromStr,
+    T::Err: fmt::Display,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(StringOrStruct(PhantomData))
+}
+
+/// Always writes the full form.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    value.serialize(serializer)
+}
+
+struct StringOrStruct<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for StringOrStruct<T>
+where
+    T: Deserialize<'de> + FromStr,
+    T::Err: fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string or a map")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        v.parse().map_err(E::custom)
+    }
+
+    // Plain scalars such as `8080`, `1.2` or `true` resolve to other types before they get
+    // here, so their text is formatted again for `FromStr`.
+    fn visit_bool<E>(self, v: bool) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<T, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        T::deserialize(MapAccessDeserializer::new(map))
+    }
+}
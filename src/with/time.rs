@@ -0,0 +1,163 @@
+//! `time` types read from YAML timestamps, which include forms such as
+//! `2001-12-14 21:59:43.10 -5` that time's own RFC 3339 parsing rejects.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use time::{Duration, OffsetDateTime};
+//!
+//! #[derive(Deserialize)]
+//! struct Job {
+//!     #[serde(with = "saphyr_serde::with::time::offset_datetime")]
+//!     started: OffsetDateTime,
+//!     #[serde(with = "saphyr_serde::with::time::duration")]
+//!     timeout: Duration,
+//! }
+//!
+//! let job: Job =
+//!     saphyr_serde::de::from_str("started: 2001-12-14 21:59:43.10 -5\ntimeout: 1:30").unwrap();
+//! assert_eq!(job.timeout, Duration::seconds(90));
+//! ```
+
+use ::time::{Date, Month};
+
+use super::parse_str;
+use crate::{
+    options::Schema,
+    scalar::{parse_int, parse_yaml11_float},
+    timestamp::{Timestamp, parse_time_of_day, parse_timestamp},
+};
+
+fn date(timestamp: &Timestamp) -> Option<Date> {
+    let month = Month::try_from(u8::try_from(timestamp.month).ok()?).ok()?;
+    Date::from_calendar_date(timestamp.year, month, u8::try_from(timestamp.day).ok()?).ok()
+}
+
+/// An `OffsetDateTime`, where a timestamp without a zone is taken to be UTC.
+pub mod offset_datetime {
+    use ::time::{
+        OffsetDateTime, PrimitiveDateTime, Time, UtcOffset, format_description::well_known::Rfc3339,
+    };
+    use serde::{Deserializer, Serializer, ser::Error};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<OffsetDateTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::parse_str(deserializer, "a YAML timestamp", |text| {
+            let timestamp = super::parse_timestamp(text)?;
+            let (time, offset) = match &timestamp.time {
+                Some(time) => (
+                    Time::from_hms_nano(
+                        u8::try_from(time.hour).ok()?,
+                        u8::try_from(time.minute).ok()?,
+                        u8::try_from(time.second).ok()?,
+                        time.nanosecond,
+                    )
+                    .ok()?,
+                    time.offset.unwrap_or(0),
+                ),
+                None => (Time::MIDNIGHT, 0),
+            };
+            let offset = UtcOffset::from_whole_seconds(offset).ok()?;
+            Some(PrimitiveDateTime::new(super::date(&timestamp)?, time).assume_offset(offset))
+        })
+    }
+
+    pub fn serialize<S>(value: &OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&value.format(&Rfc3339).map_err(S::Error::custom)?)
+    }
+}
+
+/// A `Date` written as `2001-12-14`.
+pub mod date {
+    use ::time::Date;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Date, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::parse_str(deserializer, "a YAML date", |text| {
+            let timestamp = super::parse_timestamp(text)?;
+            if timestamp.time.is_some() {
+                return None;
+            }
+            super::date(&timestamp)
+        })
+    }
+
+    pub fn serialize<S>(value: &Date, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(value)
+    }
+}
+
+/// A `Time` written as `21:59:43.10`.
+pub mod time_of_day {
+    use ::time::Time;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Time, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::parse_str(deserializer, "a time of day", |text| {
+            let time = super::parse_time_of_day(text)?;
+            Time::from_hms_nano(
+                u8::try_from(time.hour).ok()?,
+                u8::try_from(time.minute).ok()?,
+                u8::try_from(time.second).ok()?,
+                time.nanosecond,
+            )
+            .ok()
+        })
+    }
+
+    pub fn serialize<S>(value: &Time, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(value)
+    }
+}
+
+/// A `Duration` written as a number of seconds, such as `90` or `1.5`, or in the YAML 1.1 base
+/// 60 form, such as `1:30` or `1:00:00.5`.
+pub mod duration {
+    use ::time::Duration;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::parse_str(deserializer, "a number of seconds", |text| {
+            if let Ok(seconds) = super::parse_int::<i64>(text, super::Schema::Yaml12) {
+                return Some(Duration::seconds(seconds));
+            }
+            if text.contains(':')
+                && let Ok(seconds) = super::parse_int::<i64>(text, super::Schema::Yaml11)
+            {
+                return Some(Duration::seconds(seconds));
+            }
+            let seconds = super::parse_yaml11_float(text).or_else(|| text.parse().ok())?;
+            Duration::checked_seconds_f64(seconds)
+        })
+    }
+
+    pub fn serialize<S>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if value.subsec_nanoseconds() == 0 {
+            serializer.serialize_i64(value.whole_seconds())
+        } else {
+            serializer.serialize_f64(value.as_seconds_f64())
+        }
+    }
+}